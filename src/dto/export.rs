@@ -0,0 +1,437 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use chrono::{Datelike, Months, NaiveDate};
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{
+    deserialize_document, deserialize_documents, is_toml, load_holidays, load_portfolio_file,
+    price_history::history_path, LoadError, Portfolio, VestingRule,
+};
+
+/// Installment lengths tried when looking for a rule matching a schedule
+const EVERY_MONTHS: [u32; 4] = [1, 3, 6, 12];
+
+/// Dollars rounded to whole cents, as they are modeled
+fn cents(dollars: f64) -> f64 {
+    (dollars * 100.0).round() / 100.0
+}
+
+/// Whole months from `from` to `to`, if `to` is a whole number of months
+/// after `from`
+fn months_between(from: NaiveDate, to: NaiveDate) -> Option<u32> {
+    let months = (to.year() - from.year()) * 12 + to.month() as i32 - from.month() as i32;
+    let months = u32::try_from(months).ok()?;
+    (from.checked_add_months(Months::new(months)) == Some(to)).then_some(months)
+}
+
+/// The simplest rule that reproduces `events` exactly, for a grant of `total`
+/// shares or units. `events` must be sorted.
+fn find_rule(
+    commences_on: NaiveDate,
+    events: &[(NaiveDate, i32)],
+    total: i32,
+) -> Option<VestingRule> {
+    let first = months_between(commences_on, events.first()?.0)?;
+    let months = months_between(commences_on, events.last()?.0)?;
+
+    EVERY_MONTHS.iter().find_map(|every_months| {
+        let rule = VestingRule {
+            months,
+            every_months: *every_months,
+            cliff_months: if first == *every_months { 0 } else { first },
+        };
+        rule.events(commences_on, total)
+            .is_ok_and(|rule_events| rule_events == events)
+            .then_some(rule)
+    })
+}
+
+impl Portfolio {
+    /// Rewrite in canonical form: every list in date order, prices in whole
+    /// cents and vesting schedules as rules wherever a rule produces the same
+    /// events. The portfolio's models are unchanged.
+    fn canonicalize(&mut self) {
+        for psp in self.prices.iter_mut() {
+            psp.price = cents(psp.price);
+        }
+        self.prices.sort_by_key(|psp| psp.date);
+
+        for grant in self.options.iter_mut() {
+            grant.grant_value.exercise_price = cents(grant.grant_value.exercise_price);
+
+            let schedule = &mut grant.vesting_schedule;
+            if schedule.rule.is_some() {
+                // Events alongside a rule are ignored
+                schedule.events.clear();
+                continue;
+            }
+
+            schedule.events.sort_by_key(|event| event.date);
+            let Ok(events) = schedule.events(grant.grant_value.shares) else {
+                continue;
+            };
+            if let Some(rule) = find_rule(schedule.commences_on, &events, grant.grant_value.shares)
+            {
+                schedule.rule = Some(rule);
+                schedule.events.clear();
+            }
+        }
+
+        for grant in self.rsus.iter_mut() {
+            grant.grant_value.grant_price = cents(grant.grant_value.grant_price);
+            grant.grant_value.total_value = cents(grant.grant_value.total_value);
+
+            let units = grant.grant_value.to_model().units();
+            let schedule = &mut grant.vesting_schedule;
+            if schedule.rule.is_some() {
+                schedule.events.clear();
+                continue;
+            }
+
+            schedule.events.sort_by_key(|event| event.date);
+            let Ok(events) = schedule.events(units) else {
+                continue;
+            };
+            if let Some(rule) = find_rule(schedule.commences_on, &events, units) {
+                schedule.rule = Some(rule);
+                schedule.events.clear();
+            }
+        }
+
+        for scenario in self.scenarios.iter_mut() {
+            for valuation in scenario.valuations.iter_mut() {
+                valuation.price = cents(valuation.price);
+            }
+            scenario.valuations.sort_by_key(|valuation| valuation.date);
+        }
+
+        if let Some(cap_table) = &mut self.cap_table {
+            for series in cap_table.preferred.iter_mut() {
+                series.issue_price = cents(series.issue_price);
+            }
+            for round in cap_table.rounds.iter_mut() {
+                round.price = cents(round.price);
+            }
+            cap_table.rounds.sort_by_key(|round| round.date);
+        }
+
+        self.holidays.sort_by_key(|holiday| holiday.0);
+        self.holidays.dedup_by_key(|holiday| holiday.0);
+
+        self.leaves.sort_by_key(|leave| leave.starts_on);
+        self.part_time.sort_by_key(|part_time| part_time.starts_on);
+    }
+}
+
+/// The documents in a portfolio file, or none if it does not exist
+fn read_documents<T: DeserializeOwned>(path: &Path, strict: bool) -> Result<Vec<T>> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+
+    let contents =
+        fs::read_to_string(path).map_err(|_| LoadError::FileLoadFailed(path.to_path_buf()))?;
+    Ok(deserialize_documents(path, &contents, strict)?)
+}
+
+fn absolute(path: &Path) -> Result<PathBuf> {
+    fs::canonicalize(path).map_err(|_| LoadError::FileLoadFailed(path.to_path_buf()).into())
+}
+
+/// Every file of a portfolio, whether a directory or a single file. The
+/// price history is given by its absolute path.
+fn read_portfolio(portfolio_path: &Path, strict: bool) -> Result<Portfolio> {
+    if !portfolio_path.exists() {
+        Err(LoadError::PortfolioNotFound(portfolio_path.to_path_buf()))?;
+    }
+    if portfolio_path.is_file() {
+        let mut portfolio = load_portfolio_file(portfolio_path, strict)?;
+        if let Some(history) = &portfolio.price_history {
+            let path = history_path(portfolio_path, history);
+            portfolio.price_history = Some(absolute(&path)?.to_string_lossy().to_string());
+        }
+        return Ok(portfolio);
+    }
+
+    let history_path = portfolio_path.join("psp.csv");
+    let price_history = if history_path.exists() {
+        Some(absolute(&history_path)?.to_string_lossy().to_string())
+    } else {
+        None
+    };
+
+    let cap_table_path = portfolio_path.join("cap_table.yaml");
+    let cap_table = if cap_table_path.exists() {
+        let contents = fs::read_to_string(&cap_table_path)
+            .map_err(|_| LoadError::FileLoadFailed(cap_table_path.clone()))?;
+        Some(deserialize_document(&cap_table_path, &contents, strict)?)
+    } else {
+        None
+    };
+
+    Ok(Portfolio {
+        prices: read_documents(&portfolio_path.join("psp.yaml"), strict)?,
+        price_history,
+        holidays: load_holidays(portfolio_path, strict)?,
+        options: read_documents(&portfolio_path.join("option_grants.yaml"), strict)?,
+        rsus: read_documents(&portfolio_path.join("rsu_grants.yaml"), strict)?,
+        scenarios: read_documents(&portfolio_path.join("scenarios.yaml"), strict)?,
+        cap_table,
+        leaves: read_documents(&portfolio_path.join("leaves.yaml"), strict)?,
+        part_time: read_documents(&portfolio_path.join("part_time.yaml"), strict)?,
+    })
+}
+
+/// Documents separated by `---`, in the format every portfolio file is read in
+pub(super) fn to_documents<T: Serialize>(items: &[T]) -> Result<String> {
+    let documents = items
+        .iter()
+        .map(serde_yaml::to_string)
+        .collect::<Result<Vec<String>, _>>()?;
+    Ok(documents.join("---\n"))
+}
+
+/// A portfolio as a single file, in TOML if the file is named `.toml` and
+/// YAML otherwise
+fn to_portfolio_file(portfolio: &Portfolio, path: &Path) -> Result<String> {
+    if is_toml(path) {
+        Ok(toml::to_string(portfolio)?)
+    } else {
+        Ok(serde_yaml::to_string(portfolio)?)
+    }
+}
+
+fn is_portfolio_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "yaml" || extension == "yml" || extension == "toml")
+}
+
+/// Rewrite the portfolio at `portfolio_path` in canonical form, in place or to
+/// `destination`: a directory, or a single `.yaml` or `.toml` file. Writing
+/// a directory writes a file for each non-empty section. Returns the files
+/// written.
+pub fn export_portfolio(
+    portfolio_path: &Path,
+    destination: Option<&Path>,
+    strict: bool,
+) -> Result<Vec<PathBuf>> {
+    let mut portfolio = read_portfolio(portfolio_path, strict)?;
+    portfolio.canonicalize();
+
+    let destination = destination.unwrap_or(portfolio_path);
+    if is_portfolio_file(destination) {
+        // Keep the price history where it is, relative to the file if it can be
+        let directory = match destination.parent() {
+            Some(parent) if parent != Path::new("") => absolute(parent)?,
+            _ => std::env::current_dir()?,
+        };
+        if let Some(history) = &portfolio.price_history {
+            if let Ok(relative) = Path::new(history).strip_prefix(&directory) {
+                portfolio.price_history = Some(relative.to_string_lossy().to_string());
+            }
+        }
+
+        fs::write(destination, to_portfolio_file(&portfolio, destination)?)?;
+        return Ok(vec![destination.to_path_buf()]);
+    }
+
+    fs::create_dir_all(destination)?;
+    let mut written = Vec::new();
+    if let Some(history) = &portfolio.price_history {
+        let path = destination.join("psp.csv");
+        if !path.exists() || absolute(&path)? != Path::new(history) {
+            fs::copy(history, &path)?;
+            written.push(path);
+        }
+    }
+
+    let mut files: Vec<(&str, String)> = Vec::new();
+    if !portfolio.prices.is_empty() {
+        files.push(("psp.yaml", to_documents(&portfolio.prices)?));
+    }
+    if !portfolio.holidays.is_empty() {
+        files.push(("holidays.yaml", serde_yaml::to_string(&portfolio.holidays)?));
+    }
+    if !portfolio.options.is_empty() {
+        files.push(("option_grants.yaml", to_documents(&portfolio.options)?));
+    }
+    if !portfolio.rsus.is_empty() {
+        files.push(("rsu_grants.yaml", to_documents(&portfolio.rsus)?));
+    }
+    if !portfolio.scenarios.is_empty() {
+        files.push(("scenarios.yaml", to_documents(&portfolio.scenarios)?));
+    }
+    if let Some(cap_table) = &portfolio.cap_table {
+        files.push(("cap_table.yaml", serde_yaml::to_string(cap_table)?));
+    }
+    if !portfolio.leaves.is_empty() {
+        files.push(("leaves.yaml", to_documents(&portfolio.leaves)?));
+    }
+    if !portfolio.part_time.is_empty() {
+        files.push(("part_time.yaml", to_documents(&portfolio.part_time)?));
+    }
+
+    for (name, contents) in files {
+        let path = destination.join(name);
+        fs::write(&path, contents)?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model;
+
+    const PORTFOLIO: &str = "
+prices:
+  - date: 2022-01-01
+    price: 2.349
+  - date: 2021-01-01
+    price: 1.14
+options:
+  - name: New Hire
+    date: 2021-01-01
+    grant_value:
+      exercise_price: 1.14
+      shares: 4800
+    vesting_schedule:
+      commences_on: 2021-01-31
+      events:
+        - date: 2022-01-31
+          number_of_shares: 2400
+        - date: 2022-02-28
+          number_of_shares: 200
+        - date: 2022-03-31
+          number_of_shares: 200
+        - date: 2022-04-30
+          number_of_shares: 200
+        - date: 2022-05-31
+          number_of_shares: 200
+        - date: 2022-06-30
+          number_of_shares: 200
+        - date: 2022-07-31
+          number_of_shares: 200
+        - date: 2022-08-31
+          number_of_shares: 200
+        - date: 2022-09-30
+          number_of_shares: 200
+        - date: 2022-10-31
+          number_of_shares: 200
+        - date: 2022-11-30
+          number_of_shares: 200
+        - date: 2022-12-31
+          number_of_shares: 200
+        - date: 2023-01-31
+          number_of_shares: 200
+rsus:
+  - name: Refresher
+    date: 2022-01-01
+    grant_value:
+      grant_price: 2.35
+      total_value: 235.0
+    acceleration:
+      trigger: double
+    vest_price: average_5d
+    vesting_schedule:
+      commences_on: 2022-01-01
+      events:
+        - date: 2023-01-01
+          number: 60
+        - date: 2022-07-01
+          number: 40
+";
+
+    fn models(
+        portfolio: &Portfolio,
+    ) -> (
+        model::psp::PreferredStockPrice,
+        Vec<model::option::OptionGrant>,
+        Vec<model::rsu::RestrictedStockUnitGrant>,
+    ) {
+        (
+            model::psp::PreferredStockPrice::new(
+                portfolio.prices.iter().map(|p| p.to_model()).collect(),
+            ),
+            portfolio
+                .options
+                .iter()
+                .map(|g| g.to_model().unwrap())
+                .collect(),
+            portfolio
+                .rsus
+                .iter()
+                .map(|g| g.to_model().unwrap())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_find_rule() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let commences_on = date(2018, 2, 10);
+        let mut events = vec![(date(2019, 2, 10), 2500)];
+        for month in (15..=48).step_by(3) {
+            events.push((
+                commences_on.checked_add_months(Months::new(month)).unwrap(),
+                625,
+            ));
+        }
+
+        assert_eq!(
+            Some(VestingRule {
+                months: 48,
+                every_months: 3,
+                cliff_months: 12,
+            }),
+            find_rule(commences_on, &events, 10000)
+        );
+
+        events[1].1 += 1;
+        assert_eq!(None, find_rule(commences_on, &events, 10000));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let path = Path::new("portfolio.yaml");
+        let mut portfolio: Portfolio = deserialize_document(path, PORTFOLIO, true).unwrap();
+        let loaded = models(&portfolio);
+
+        portfolio.canonicalize();
+        assert_eq!(
+            Some(VestingRule {
+                months: 24,
+                every_months: 1,
+                cliff_months: 12,
+            }),
+            portfolio.options[0].vesting_schedule.rule
+        );
+        assert!(portfolio.rsus[0].vesting_schedule.rule.is_none());
+
+        let yaml = to_portfolio_file(&portfolio, path).unwrap();
+        let exported: Portfolio = deserialize_document(path, &yaml, true).unwrap();
+        assert_eq!(loaded, models(&exported));
+
+        let toml_path = Path::new("portfolio.toml");
+        let toml = to_portfolio_file(&portfolio, toml_path).unwrap();
+        let exported = super::super::deserialize_toml(toml_path, &toml, true).unwrap();
+        assert_eq!(loaded, models(&exported));
+
+        let documents = to_documents(&portfolio.rsus).unwrap();
+        let exported: Vec<super::super::RestrictedStockUnitGrant> =
+            deserialize_documents(Path::new("rsu_grants.yaml"), &documents, true).unwrap();
+        assert_eq!(
+            loaded.2,
+            exported
+                .iter()
+                .map(|g| g.to_model().unwrap())
+                .collect::<Vec<_>>()
+        );
+    }
+}
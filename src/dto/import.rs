@@ -0,0 +1,641 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{
+    deserialize_documents, source, LoadError, OptionGrant, OptionGrantValue,
+    OptionGrantVestingEvent, OptionGrantVestingSchedule, RestrictedStockUnitGrant,
+    RestrictedStockUnitGrantValue, RestrictedStockUnitVestingEvent,
+    RestrictedStockUnitVestingSchedule,
+};
+use crate::model;
+
+/// Equity administration platforms whose CSV exports can be imported
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImportFormat {
+    /// Carta's stock option and RSU grant exports
+    Carta,
+
+    /// Shareworks' grant summary exports
+    Shareworks,
+
+    /// E*Trade's benefit history exports
+    ETrade,
+}
+
+/// Column headers used by a platform's export. Each column may go by several
+/// names; the first found is used.
+struct Layout {
+    format: ImportFormat,
+    id: &'static [&'static str],
+    kind: &'static [&'static str],
+    granted_on: &'static [&'static str],
+    commences_on: &'static [&'static str],
+    price: &'static [&'static str],
+    granted: &'static [&'static str],
+    vest_date: &'static [&'static str],
+    vested: &'static [&'static str],
+}
+
+const LAYOUTS: [Layout; 3] = [
+    Layout {
+        format: ImportFormat::Carta,
+        id: &["Security ID", "Security", "Option Grant ID"],
+        kind: &["Security Type", "Award Type"],
+        granted_on: &["Issue Date", "Grant Date"],
+        commences_on: &["Vesting Start Date", "Vesting Start"],
+        price: &["Exercise Price", "Issue Price", "Price Per Share"],
+        granted: &["Quantity Issued", "Quantity"],
+        vest_date: &["Vest Date", "Vesting Date"],
+        vested: &["Quantity Vesting", "Vesting Quantity", "Shares Vesting"],
+    },
+    Layout {
+        format: ImportFormat::Shareworks,
+        id: &["Grant Name", "Grant ID"],
+        kind: &["Award Type", "Grant Type"],
+        granted_on: &["Grant Date"],
+        commences_on: &["Vest From Date", "Vesting Start Date"],
+        price: &["Grant Price", "Exercise Price"],
+        granted: &["Granted", "Quantity Granted"],
+        vest_date: &["Vest Date", "Vesting Date"],
+        vested: &["Quantity Vesting", "Vesting Quantity", "Vest Quantity"],
+    },
+    Layout {
+        format: ImportFormat::ETrade,
+        id: &["Grant Number"],
+        kind: &["Plan Type", "Grant Type"],
+        granted_on: &["Grant Date"],
+        commences_on: &["Vest Start Date"],
+        price: &["Grant Price", "Exercise Price"],
+        granted: &["Granted Qty.", "Granted Qty", "Granted Quantity"],
+        vest_date: &["Vest Date", "Vest Period Date"],
+        vested: &["Vest Qty.", "Vest Qty", "Vested Qty."],
+    },
+];
+
+/// Where each column of a layout is in the export's header row
+struct Columns {
+    id: usize,
+    kind: Option<usize>,
+    granted_on: usize,
+    commences_on: Option<usize>,
+    price: Option<usize>,
+    granted: Option<usize>,
+    vest_date: usize,
+    vested: usize,
+}
+
+fn find(headers: &csv::StringRecord, names: &[&str]) -> Option<usize> {
+    names.iter().find_map(|name| {
+        headers
+            .iter()
+            .position(|header| header.trim().eq_ignore_ascii_case(name))
+    })
+}
+
+impl Layout {
+    fn columns(&self, headers: &csv::StringRecord) -> Option<Columns> {
+        Some(Columns {
+            id: find(headers, self.id)?,
+            kind: find(headers, self.kind),
+            granted_on: find(headers, self.granted_on)?,
+            commences_on: find(headers, self.commences_on),
+            price: find(headers, self.price),
+            granted: find(headers, self.granted),
+            vest_date: find(headers, self.vest_date)?,
+            vested: find(headers, self.vested)?,
+        })
+    }
+}
+
+/// A grant as read from the rows of an export, one row per vesting event
+struct ImportedGrant {
+    id: String,
+    is_rsu: bool,
+    granted_on: NaiveDate,
+
+    /// When vesting commences, if the export has it
+    commences_on: Option<NaiveDate>,
+    price: Option<f64>,
+    granted: Option<i32>,
+
+    /// 1-based line of the grant's first row
+    line: usize,
+    events: Vec<(NaiveDate, i32)>,
+}
+
+impl ImportedGrant {
+    fn add_event(&mut self, date: NaiveDate, number: i32) {
+        match self.events.iter_mut().find(|(on, _)| *on == date) {
+            Some((_, total)) => *total += number,
+            None => self.events.push((date, number)),
+        }
+    }
+
+    fn granted(&self) -> i32 {
+        self.granted
+            .unwrap_or_else(|| self.events.iter().map(|(_, number)| number).sum())
+    }
+}
+
+/// Security types written for RSUs, e.g. `RSU`, `Restricted Stock Unit` or
+/// E*Trade's `RS`. Everything else is an option.
+fn is_rsu(kind: &str) -> bool {
+    let kind = kind.trim().to_ascii_uppercase();
+    kind.contains("RSU") || kind.contains("RESTRICTED STOCK UNIT") || kind == "RS"
+}
+
+pub(super) fn parse_date(value: &str) -> Option<NaiveDate> {
+    ["%Y-%m-%d", "%m/%d/%Y", "%m/%d/%y", "%d-%b-%Y", "%b %d, %Y"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value.trim(), format).ok())
+}
+
+/// A number written with an optional currency symbol and thousands separators
+pub(super) fn parse_number(value: &str) -> Option<f64> {
+    let value: String = value
+        .chars()
+        .filter(|c| !matches!(c, '$' | ',' | ' '))
+        .collect();
+    value.parse().ok()
+}
+
+fn read_grants(csv_path: &Path, format: Option<ImportFormat>) -> Result<Vec<ImportedGrant>> {
+    let invalid =
+        |line: usize, message: String| LoadError::InvalidCsv(csv_path.to_path_buf(), line, message);
+
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_path(csv_path)
+        .map_err(|_| LoadError::FileLoadFailed(csv_path.to_path_buf()))?;
+    let headers = reader
+        .headers()
+        .map_err(|e| invalid(1, e.to_string()))?
+        .clone();
+
+    let columns = LAYOUTS
+        .iter()
+        .filter(|layout| format.is_none_or(|format| format == layout.format))
+        .find_map(|layout| layout.columns(&headers))
+        .ok_or_else(|| LoadError::UnknownCsvLayout(csv_path.to_path_buf()))?;
+
+    let mut grants: Vec<ImportedGrant> = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| invalid(0, e.to_string()))?;
+        let line = record
+            .position()
+            .map_or(0, |position| position.line() as usize);
+        let field = |column: usize| record.get(column).unwrap_or("").trim();
+
+        let id = field(columns.id);
+        if id.is_empty() {
+            continue;
+        }
+
+        let date = |column: usize| {
+            parse_date(field(column))
+                .ok_or_else(|| invalid(line, format!("invalid date {:?}", field(column))))
+        };
+        let number = |column: usize| {
+            parse_number(field(column))
+                .ok_or_else(|| invalid(line, format!("invalid number {:?}", field(column))))
+        };
+        let quantity = |column: usize| {
+            number(column).and_then(|n| {
+                if n >= 0.0 && n.fract() == 0.0 && n <= i32::MAX as f64 {
+                    Ok(n as i32)
+                } else {
+                    Err(invalid(
+                        line,
+                        format!("expected a whole number of shares, got {:?}", field(column)),
+                    ))
+                }
+            })
+        };
+        let optional = |column: Option<usize>| column.filter(|column| !field(*column).is_empty());
+
+        let vest_date = date(columns.vest_date)?;
+        let vested = quantity(columns.vested)?;
+
+        let grant = match grants.iter().position(|grant| grant.id == id) {
+            Some(i) => &mut grants[i],
+            None => {
+                let granted_on = date(columns.granted_on)?;
+                grants.push(ImportedGrant {
+                    id: id.to_string(),
+                    is_rsu: columns.kind.is_some_and(|column| is_rsu(field(column))),
+                    granted_on,
+                    commences_on: optional(columns.commences_on).map(date).transpose()?,
+                    price: optional(columns.price).map(number).transpose()?,
+                    granted: optional(columns.granted).map(quantity).transpose()?,
+                    line,
+                    events: Vec::new(),
+                });
+                grants.last_mut().unwrap()
+            }
+        };
+        grant.add_event(vest_date, vested);
+    }
+
+    for grant in grants.iter_mut() {
+        grant.events.sort();
+    }
+
+    Ok(grants)
+}
+
+/// The grants in a portfolio file, or none if it does not exist
+fn read_existing<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+
+    let contents =
+        fs::read_to_string(path).map_err(|_| LoadError::FileLoadFailed(path.to_path_buf()))?;
+    Ok(deserialize_documents(path, &contents, false)?)
+}
+
+/// Which existing grant an imported grant updates: one with the same name,
+/// or else the only unmatched grant made on the same day
+fn matching(
+    existing: &[(String, NaiveDate)],
+    matched: &[usize],
+    grant: &ImportedGrant,
+) -> Option<usize> {
+    existing
+        .iter()
+        .position(|(name, _)| *name == grant.id)
+        .or_else(|| {
+            let same_day: Vec<usize> = (0..existing.len())
+                .filter(|i| !matched.contains(i) && existing[*i].1 == grant.granted_on)
+                .collect();
+            match same_day[..] {
+                [i] => Some(i),
+                _ => None,
+            }
+        })
+}
+
+/// Grants imported from an export, merged into the portfolio's grants
+pub struct Import {
+    option_grants: Vec<OptionGrant>,
+    rsu_grants: Vec<RestrictedStockUnitGrant>,
+
+    /// How many of the grants were in the grant files before the import
+    existing_options: usize,
+    existing_rsus: usize,
+
+    /// Indexes of the grants the import adds or changes
+    changed_options: Vec<usize>,
+    changed_rsus: Vec<usize>,
+}
+
+/// Import the grants in an equity platform's CSV export, detecting its layout
+/// unless `format` is given. Imported grants update the portfolio's grant of
+/// the same name, or made on the same day, and are otherwise added. What the
+/// export leaves out is kept from the grant being updated; vesting otherwise
+/// commences on the grant date, and RSU grants are priced at the preferred
+/// stock price on the grant date.
+pub fn import_grants(
+    csv_path: &Path,
+    format: Option<ImportFormat>,
+    portfolio_path: &Path,
+    psp: &model::psp::PreferredStockPrice,
+) -> Result<Import> {
+    let grants = read_grants(csv_path, format)?;
+
+    let mut option_grants: Vec<OptionGrant> =
+        read_existing(&portfolio_path.join("option_grants.yaml"))?;
+    let mut rsu_grants: Vec<RestrictedStockUnitGrant> =
+        read_existing(&portfolio_path.join("rsu_grants.yaml"))?;
+    let existing_options: Vec<(String, NaiveDate)> = option_grants
+        .iter()
+        .map(|grant| (grant.name.clone(), grant.date))
+        .collect();
+    let existing_rsus: Vec<(String, NaiveDate)> = rsu_grants
+        .iter()
+        .map(|grant| (grant.name.clone(), grant.date))
+        .collect();
+
+    let missing = |grant: &ImportedGrant, message: String| {
+        LoadError::InvalidCsv(csv_path.to_path_buf(), grant.line, message)
+    };
+
+    let mut matched_options = Vec::new();
+    let mut matched_rsus = Vec::new();
+    let mut changed_options = Vec::new();
+    let mut changed_rsus = Vec::new();
+    for grant in &grants {
+        if grant.is_rsu {
+            let i = matching(&existing_rsus, &matched_rsus, grant);
+            let existing = i.map(|i| &rsu_grants[i]);
+
+            let grant_price = match (grant.price, existing) {
+                (Some(price), _) => price,
+                (None, Some(existing)) => existing.grant_value.grant_price,
+                (None, None) => psp
+                    .value_on(&grant.granted_on)
+                    .map_or(0.0, |cents| cents as f64 / 100.0),
+            };
+            if grant_price <= 0.0 {
+                Err(missing(
+                    grant,
+                    format!(
+                        "{}: no grant price, and no preferred stock price on {}",
+                        grant.id, grant.granted_on
+                    ),
+                ))?;
+            }
+
+            let grant_value = RestrictedStockUnitGrantValue {
+                grant_price,
+                total_value: grant_price * grant.granted() as f64,
+            };
+            let vesting_schedule = RestrictedStockUnitVestingSchedule {
+                commences_on: grant
+                    .commences_on
+                    .or(existing.map(|existing| existing.vesting_schedule.commences_on))
+                    .unwrap_or(grant.granted_on),
+                rule: None,
+                events: grant
+                    .events
+                    .iter()
+                    .map(|(date, number)| RestrictedStockUnitVestingEvent {
+                        date: *date,
+                        number: *number,
+                    })
+                    .collect(),
+            };
+
+            match i {
+                Some(i) => {
+                    matched_rsus.push(i);
+                    let existing = &mut rsu_grants[i];
+                    let before = existing.to_model();
+                    existing.date = grant.granted_on;
+                    existing.grant_value = grant_value;
+                    existing.vesting_schedule = vesting_schedule;
+                    if existing.to_model() != before {
+                        changed_rsus.push(i);
+                    }
+                }
+                None => {
+                    changed_rsus.push(rsu_grants.len());
+                    rsu_grants.push(RestrictedStockUnitGrant {
+                        name: grant.id.clone(),
+                        date: grant.granted_on,
+                        grant_value,
+                        vesting_schedule,
+                        acceleration: None,
+                        vest_price: Default::default(),
+                    });
+                }
+            }
+        } else {
+            let i = matching(&existing_options, &matched_options, grant);
+            let existing = i.map(|i| &option_grants[i]);
+
+            let exercise_price = match (grant.price, existing) {
+                (Some(price), _) => price,
+                (None, Some(existing)) => existing.grant_value.exercise_price,
+                (None, None) => Err(missing(grant, format!("{}: no exercise price", grant.id)))?,
+            };
+
+            let grant_value = OptionGrantValue {
+                exercise_price,
+                shares: grant.granted(),
+            };
+            let vesting_schedule = OptionGrantVestingSchedule {
+                commences_on: grant
+                    .commences_on
+                    .or(existing.map(|existing| existing.vesting_schedule.commences_on))
+                    .unwrap_or(grant.granted_on),
+                rule: None,
+                events: grant
+                    .events
+                    .iter()
+                    .map(|(date, number)| OptionGrantVestingEvent {
+                        date: *date,
+                        number_of_shares: *number,
+                    })
+                    .collect(),
+            };
+
+            match i {
+                Some(i) => {
+                    matched_options.push(i);
+                    let existing = &mut option_grants[i];
+                    let before = existing.to_model();
+                    existing.date = grant.granted_on;
+                    existing.grant_value = grant_value;
+                    existing.vesting_schedule = vesting_schedule;
+                    if existing.to_model() != before {
+                        changed_options.push(i);
+                    }
+                }
+                None => {
+                    changed_options.push(option_grants.len());
+                    option_grants.push(OptionGrant {
+                        name: grant.id.clone(),
+                        date: grant.granted_on,
+                        grant_value,
+                        vesting_schedule,
+                        post_termination_exercise_period: Default::default(),
+                        expires_on: None,
+                        acceleration: None,
+                        vest_price: Default::default(),
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(Import {
+        option_grants,
+        rsu_grants,
+        existing_options: existing_options.len(),
+        existing_rsus: existing_rsus.len(),
+        changed_options,
+        changed_rsus,
+    })
+}
+
+impl Import {
+    /// The option grants after the import
+    pub fn option_grants(&self) -> Result<Vec<model::option::OptionGrant>> {
+        self.option_grants
+            .iter()
+            .map(|g| g.to_model().map_err(anyhow::Error::msg))
+            .collect()
+    }
+
+    /// The RSU grants after the import
+    pub fn rsu_grants(&self) -> Result<Vec<model::rsu::RestrictedStockUnitGrant>> {
+        self.rsu_grants
+            .iter()
+            .map(|g| g.to_model().map_err(anyhow::Error::msg))
+            .collect()
+    }
+
+    /// Write the grants the import adds or changes to the grant files,
+    /// returning the files written. Changed grants replace their documents and
+    /// new grants are appended, leaving the rest of each file as it was.
+    pub fn write(&self, portfolio_path: &Path) -> Result<Vec<PathBuf>> {
+        let mut written = Vec::new();
+
+        if !self.changed_options.is_empty() {
+            let path = portfolio_path.join("option_grants.yaml");
+            write_changes(
+                &path,
+                &self.option_grants,
+                self.existing_options,
+                &self.changed_options,
+            )?;
+            written.push(path);
+        }
+        if !self.changed_rsus.is_empty() {
+            let path = portfolio_path.join("rsu_grants.yaml");
+            write_changes(
+                &path,
+                &self.rsu_grants,
+                self.existing_rsus,
+                &self.changed_rsus,
+            )?;
+            written.push(path);
+        }
+
+        Ok(written)
+    }
+}
+
+/// Write the grants at `changed` indexes to the grant file at `path`, which
+/// held the first `existing` of `grants`
+fn write_changes<T: Serialize>(
+    path: &Path,
+    grants: &[T],
+    existing: usize,
+    changed: &[usize],
+) -> Result<()> {
+    let contents = if path.exists() {
+        fs::read_to_string(path).map_err(|_| LoadError::FileLoadFailed(path.to_path_buf()))?
+    } else {
+        String::new()
+    };
+
+    let mut replaced = Vec::new();
+    let mut added = Vec::new();
+    for &i in changed {
+        let text = serde_yaml::to_string(&grants[i])?;
+        if i < existing {
+            replaced.push((i, text));
+        } else {
+            added.push(text);
+        }
+    }
+
+    fs::write(path, source::splice_documents(&contents, &replaced, &added))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_read_carta_export() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.csv");
+        fs::write(
+            &path,
+            "Security ID,Security Type,Issue Date,Vesting Start Date,Exercise Price,Quantity Issued,Vest Date,Quantity Vesting\n\
+             ES-1,Option,2021-06-15,2021-06-01,$1.50,\"4,800\",2022-06-01,\"1,200\"\n\
+             ES-1,Option,2021-06-15,2021-06-01,$1.50,\"4,800\",07/01/2022,100\n\
+             ES-1,Option,2021-06-15,2021-06-01,$1.50,\"4,800\",07/01/2022,100\n\
+             RSU-2,RSU,2022-01-10,,,400,2023-01-10,400\n",
+        )
+        .unwrap();
+
+        let grants = read_grants(&path, None).unwrap();
+
+        assert_eq!(grants.len(), 2);
+
+        let option = &grants[0];
+        assert!(!option.is_rsu);
+        assert_eq!(option.price, Some(1.5));
+        assert_eq!(option.granted(), 4800);
+        assert_eq!(option.line, 2);
+        assert_eq!(
+            option.events,
+            vec![
+                (NaiveDate::from_ymd_opt(2022, 6, 1).unwrap(), 1200),
+                (NaiveDate::from_ymd_opt(2022, 7, 1).unwrap(), 200),
+            ]
+        );
+
+        let rsu = &grants[1];
+        assert!(rsu.is_rsu);
+        assert_eq!(rsu.price, None);
+        assert_eq!(rsu.commences_on, None);
+    }
+
+    #[test]
+    fn test_write_keeps_unchanged_grants() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let existing = "# Grants from the offer letter\n\
+                        name: RSU-1\n\
+                        date: 2022-01-10\n\
+                        grant_value:\n  grant_price: 2.0\n  total_value: 800\n\
+                        vesting_schedule:\n  commences_on: 2022-01-10\n  \
+                        # all at once\n  events:\n    - date: 2023-01-10\n      number: 400\n";
+        fs::write(dir.join("rsu_grants.yaml"), existing).unwrap();
+        let csv_path = dir.join("export.csv");
+        fs::write(
+            &csv_path,
+            "Security ID,Security Type,Issue Date,Vesting Start Date,Issue Price,Quantity Issued,Vest Date,Quantity Vesting\n\
+             RSU-1,RSU,2022-01-10,2022-01-10,$2.00,400,2023-01-10,400\n\
+             RSU-2,RSU,2023-01-10,2023-01-10,$2.30,100,2024-01-10,100\n",
+        )
+        .unwrap();
+
+        let psp = model::psp::PreferredStockPrice::new(vec![]);
+        let import = import_grants(&csv_path, None, dir, &psp).unwrap();
+        let written = import.write(dir).unwrap();
+        let contents = fs::read_to_string(dir.join("rsu_grants.yaml")).unwrap();
+
+        assert_eq!(vec![dir.join("rsu_grants.yaml")], written);
+        let (unchanged, added) = contents.split_once("---\n").unwrap();
+        assert_eq!(existing, unchanged);
+        assert!(added.starts_with("name: RSU-2\n"));
+        assert!(added.contains("grant_price: 2.3\n"));
+    }
+
+    #[test]
+    fn test_invalid_quantity() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.csv");
+        let header = "Security ID,Security Type,Issue Date,Vesting Start Date,Issue Price,Quantity Issued,Vest Date,Quantity Vesting\n";
+
+        for (granted, vesting) in [("400", "-400"), ("400", "399.5"), ("400.5", "400")] {
+            fs::write(
+                &path,
+                format!(
+                    "{}RSU-1,RSU,2022-01-10,2022-01-10,$2.00,{},2023-01-10,{}\n",
+                    header, granted, vesting
+                ),
+            )
+            .unwrap();
+
+            let error = read_grants(&path, None).err().unwrap();
+            assert!(matches!(
+                error.downcast_ref(),
+                Some(LoadError::InvalidCsv(_, 2, _))
+            ));
+        }
+    }
+}
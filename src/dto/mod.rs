@@ -1,21 +1,50 @@
 use std::{
     fs,
+    num::NonZeroU32,
     path::{Path, PathBuf},
 };
 
 use anyhow::Context;
-use chrono::NaiveDate;
-use serde::Deserialize;
-use serde_yaml::Deserializer;
+use chrono::{Months, NaiveDate};
+use schemars::JsonSchema;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::model;
 
+/// Writing portfolios in canonical form
+pub mod export;
+
+/// Importing grants from equity platform CSV exports
+pub mod import;
+mod price_history;
+mod source;
+
+/// Checking portfolio files for problems
+pub mod validate;
+
 mod naive_date_format {
     use chrono::NaiveDate;
-    use serde::{self, Deserialize, Deserializer};
+    use serde::{self, Deserialize, Deserializer, Serializer};
 
     const FORMAT: &str = "%Y-%m-%d";
 
+    pub fn serialize<S>(date: &NaiveDate, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&date.format(FORMAT).to_string())
+    }
+
+    pub fn serialize_option<S>(date: &Option<NaiveDate>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match date {
+            Some(date) => serialize(date, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
     pub fn deserialize<'de, D>(deserializer: D) -> Result<NaiveDate, D::Error>
     where
         D: Deserializer<'de>,
@@ -23,202 +52,1623 @@ mod naive_date_format {
         let s = String::deserialize(deserializer)?;
         NaiveDate::parse_from_str(&s, FORMAT).map_err(serde::de::Error::custom)
     }
+
+    pub fn deserialize_option<'de, D>(deserializer: D) -> Result<Option<NaiveDate>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(s) => NaiveDate::parse_from_str(&s, FORMAT)
+                .map(Some)
+                .map_err(serde::de::Error::custom),
+            None => Ok(None),
+        }
+    }
+}
+
+mod exercise_period_format {
+    use serde::{self, Deserialize, Deserializer, Serializer};
+
+    use crate::model::option::ExercisePeriod;
+
+    /// Parse a period such as `90d`, `6m` or `7y`
+    pub fn parse(s: &str) -> Option<ExercisePeriod> {
+        let (number, unit) = s.trim().split_at(s.trim().len().checked_sub(1)?);
+        let number: u32 = number.trim().parse().ok()?;
+        match unit {
+            "d" => Some(ExercisePeriod::Days(number)),
+            "m" => Some(ExercisePeriod::Months(number)),
+            "y" => Some(ExercisePeriod::Months(number.checked_mul(12)?)),
+            _ => None,
+        }
+    }
+
+    pub fn is_default(period: &ExercisePeriod) -> bool {
+        period == &ExercisePeriod::default()
+    }
+
+    /// Format a period the way it is written, e.g. `90d` or `7y`
+    pub fn format(period: &ExercisePeriod) -> String {
+        match period {
+            ExercisePeriod::Days(days) => format!("{}d", days),
+            ExercisePeriod::Months(months) if months % 12 == 0 => format!("{}y", months / 12),
+            ExercisePeriod::Months(months) => format!("{}m", months),
+        }
+    }
+
+    pub fn serialize<S>(period: &ExercisePeriod, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&format(period))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<ExercisePeriod, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        parse(&s).ok_or_else(|| {
+            serde::de::Error::custom(format!(
+                "invalid period {:?}, expected a number of days, months or years such as 90d, 6m or 7y",
+                s
+            ))
+        })
+    }
 }
 
+mod vest_price_format {
+    use serde::{self, Deserialize, Deserializer, Serializer};
+
+    use crate::model::trading::VestPriceRule;
+
+    /// Parse a rule such as `close`, `prior_close` or `average_5d`
+    pub fn parse(s: &str) -> Option<VestPriceRule> {
+        match s.trim() {
+            "close" => Some(VestPriceRule::Close),
+            "prior_close" => Some(VestPriceRule::PriorClose),
+            s => {
+                let days = s.strip_prefix("average_")?.strip_suffix('d')?;
+                days.parse()
+                    .ok()
+                    .filter(|days| *days > 0)
+                    .map(VestPriceRule::Average)
+            }
+        }
+    }
+
+    pub fn is_default(rule: &VestPriceRule) -> bool {
+        rule == &VestPriceRule::default()
+    }
+
+    pub fn format(rule: &VestPriceRule) -> String {
+        match rule {
+            VestPriceRule::Close => "close".to_string(),
+            VestPriceRule::PriorClose => "prior_close".to_string(),
+            VestPriceRule::Average(days) => format!("average_{}d", days),
+        }
+    }
+
+    pub fn serialize<S>(rule: &VestPriceRule, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&format(rule))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<VestPriceRule, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        parse(&s).ok_or_else(|| {
+            serde::de::Error::custom(format!(
+                "invalid vest price {:?}, expected close, prior_close or a number of trading days to average such as average_5d",
+                s
+            ))
+        })
+    }
+}
+
+/// Errors from loading portfolio files carry a [`LoadError`] with context
 pub type Result<T> = anyhow::Result<T>;
 
+/// Why a portfolio file could not be loaded
 #[derive(Debug, thiserror::Error)]
 pub enum LoadError {
+    /// The file could not be read
     #[error("unable to load file at {0}")]
     FileLoadFailed(PathBuf),
 
-    #[error("deserialization from expected YAML structure failed")]
-    DeserializationFailed(#[from] serde_yaml::Error),
+    /// A document did not match what its file holds
+    #[error("{0}")]
+    DeserializationFailed(Box<source::DocumentError>),
+
+    /// A TOML file could not be parsed, with the parser's message
+    #[error("{1}\n  --> {0}")]
+    InvalidToml(PathBuf, String),
+
+    /// A CSV record could not be read, with its line and why
+    #[error("{0}:{1}: {2}")]
+    InvalidCsv(PathBuf, usize, String),
+
+    /// A value is well formed but out of range, with why
+    #[error("{0}: {1}")]
+    InvalidValue(PathBuf, String),
+
+    /// The CSV header does not match any export layout
+    #[error("unrecognized CSV export at {0}, expected a Carta, Shareworks or E*Trade layout")]
+    UnknownCsvLayout(PathBuf),
+
+    /// Neither a portfolio directory nor a single-file portfolio exists
+    #[error("no portfolio found at {0}")]
+    PortfolioNotFound(PathBuf),
+
+    /// Grants were found without any prices to value them at
+    #[error("{0} has grants but no prices; add psp.yaml, or a prices section to a single-file portfolio")]
+    PricesMissing(PathBuf),
 }
 
-#[derive(Debug, Deserialize)]
+/// Where a portfolio is loaded from: a directory of files, or a single
+/// portfolio file, which is read and parsed once when opened
+pub struct PortfolioFiles {
+    path: PathBuf,
+    strict: bool,
+
+    /// The parsed portfolio, when `path` is a single file
+    file: Option<Portfolio>,
+}
+
+impl PortfolioFiles {
+    /// Open the portfolio at `path`, a directory or a single portfolio.yaml or
+    /// portfolio.toml. When `strict`, keys that do not match any field are an
+    /// error rather than ignored.
+    pub fn open(path: &Path, strict: bool) -> Result<PortfolioFiles> {
+        let file = if path.is_file() {
+            Some(load_portfolio_file(path, strict)?)
+        } else {
+            None
+        };
+
+        Ok(PortfolioFiles {
+            path: path.to_path_buf(),
+            strict,
+            file,
+        })
+    }
+
+    /// The directory or file the portfolio is loaded from
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether keys that do not match any field are an error
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+}
+
+/// Deserialize `document`, read from `path`. When `strict`, keys that do not
+/// match any field are an error rather than ignored.
+fn deserialize_in<T: DeserializeOwned>(
+    path: &Path,
+    document: &source::Document,
+    strict: bool,
+) -> std::result::Result<T, LoadError> {
+    let mut unknown_keys: Vec<String> = Vec::new();
+    let value: T = serde_ignored::deserialize(
+        serde_yaml::Deserializer::from_str(document.text),
+        |key_path| unknown_keys.push(key_path.to_string()),
+    )
+    .map_err(|e| {
+        LoadError::DeserializationFailed(Box::new(source::DocumentError::new(path, document, &e)))
+    })?;
+
+    match unknown_keys.first() {
+        Some(key_path) if strict => Err(LoadError::DeserializationFailed(Box::new(
+            source::DocumentError::unknown_key(path, document, key_path),
+        ))),
+        _ => Ok(value),
+    }
+}
+
+/// One document of a multi-document YAML file, deserialized
+struct Deserialized<'a, T> {
+    document: source::Document<'a>,
+    value: T,
+
+    /// Path of each key that did not match any field
+    unknown_keys: Vec<String>,
+}
+
+/// Deserialize each document in `contents`, read from `path`, skipping any
+/// that are empty. serde_yaml splits the documents; [`source::documents`] only
+/// locates them for error messages. A syntax error ends the file.
+fn deserialize_each<'a, T: DeserializeOwned>(
+    path: &Path,
+    contents: &'a str,
+) -> Vec<std::result::Result<Deserialized<'a, T>, Box<source::DocumentError>>> {
+    let mut parsed: Vec<std::result::Result<(T, Vec<String>), serde_yaml::Error>> = Vec::new();
+    for document in serde_yaml::Deserializer::from_str(contents) {
+        let mut unknown_keys: Vec<String> = Vec::new();
+        let value: std::result::Result<Option<T>, _> =
+            serde_ignored::deserialize(document, |key_path| {
+                unknown_keys.push(key_path.to_string())
+            });
+        match value {
+            Ok(Some(value)) => parsed.push(Ok((value, unknown_keys))),
+            Ok(None) => {}
+            Err(e) => {
+                // serde_yaml gives the same syntax error for every document
+                // after the one it is in
+                let repeated = matches!(parsed.last(), Some(Err(previous))
+                    if previous.to_string() == e.to_string());
+                if repeated {
+                    break;
+                }
+                parsed.push(Err(e));
+            }
+        }
+    }
+
+    let located = source::documents(contents);
+    // Only trust the located documents when they line up with serde_yaml's
+    let aligned = located.len() == parsed.len();
+    let whole_file = |index| source::Document {
+        index,
+        line: 1,
+        text: contents,
+    };
+    parsed
+        .into_iter()
+        .enumerate()
+        .map(|(index, parsed)| {
+            let document = if aligned {
+                located[index].clone()
+            } else {
+                whole_file(index)
+            };
+            match parsed {
+                Ok((value, unknown_keys)) => Ok(Deserialized {
+                    document,
+                    value,
+                    unknown_keys,
+                }),
+                Err(e) => {
+                    let document = e
+                        .location()
+                        .and_then(|location| source::document_at(&located, location.line()))
+                        .map_or(document, |document| source::Document {
+                            index,
+                            ..document.clone()
+                        });
+                    Err(Box::new(source::DocumentError::new(path, &document, &e)))
+                }
+            }
+        })
+        .collect()
+}
+
+/// Deserialize every document in `contents`, read from `path`. When `strict`,
+/// keys that do not match any field are an error rather than ignored.
+fn deserialize_documents<T: DeserializeOwned>(
+    path: &Path,
+    contents: &str,
+    strict: bool,
+) -> std::result::Result<Vec<T>, LoadError> {
+    deserialize_each(path, contents)
+        .into_iter()
+        .map(|parsed| {
+            let parsed = parsed.map_err(LoadError::DeserializationFailed)?;
+            match parsed.unknown_keys.first() {
+                Some(key_path) if strict => Err(LoadError::DeserializationFailed(Box::new(
+                    source::DocumentError::unknown_key(path, &parsed.document, key_path),
+                ))),
+                _ => Ok(parsed.value),
+            }
+        })
+        .collect()
+}
+
+/// Deserialize `contents`, read from `path`, as a single document
+fn deserialize_document<T: DeserializeOwned>(
+    path: &Path,
+    contents: &str,
+    strict: bool,
+) -> std::result::Result<T, LoadError> {
+    let document = source::Document {
+        index: 0,
+        line: 1,
+        text: contents,
+    };
+    deserialize_in(path, &document, strict)
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 struct PreferredStockPrice {
     #[serde(with = "naive_date_format")]
+    #[schemars(with = "NaiveDate")]
     date: NaiveDate,
     price: f64,
 }
 
 impl PreferredStockPrice {
     pub fn to_model(&self) -> model::psp::PreferredStockPriceValuation {
-        model::psp::PreferredStockPriceValuation::new(self.date, (self.price * 100.0) as i32)
+        model::psp::PreferredStockPriceValuation::new(
+            self.date,
+            (self.price * 100.0).round() as i32,
+        )
     }
 }
 
-pub fn load_psp(portfolio_path: &Path) -> Result<model::psp::PreferredStockPrice> {
-    let psp_path = portfolio_path.join("psp.yaml");
+/// A day the market is closed, other than weekends
+#[derive(Debug, Deserialize, Serialize)]
+struct Holiday(#[serde(with = "naive_date_format")] NaiveDate);
 
-    let contents =
-        fs::read_to_string(&psp_path).map_err(|_| LoadError::FileLoadFailed(psp_path.clone()))?;
+/// Load the holidays in `holidays.yaml`, if there is one
+fn load_holidays(portfolio_path: &Path, strict: bool) -> Result<Vec<Holiday>> {
+    let holidays_path = portfolio_path.join("holidays.yaml");
+    if !holidays_path.exists() {
+        return Ok(vec![]);
+    }
 
-    let mut result: Vec<PreferredStockPrice> = Vec::new();
-    for doc in Deserializer::from_str(&contents) {
-        let psp = PreferredStockPrice::deserialize(doc)
-            .map_err(LoadError::DeserializationFailed)
-            .with_context(|| {
-                format!(
-                    "Preferred Stock Price deserialize failed from {:?}",
-                    &psp_path
-                )
-            })?;
+    let contents = fs::read_to_string(&holidays_path)
+        .map_err(|_| LoadError::FileLoadFailed(holidays_path.clone()))?;
+
+    deserialize_document(&holidays_path, &contents, strict)
+        .with_context(|| format!("Holidays deserialize failed from {:?}", &holidays_path))
+}
+
+/// Load the preferred stock price from `psp.yaml`, merged with any daily
+/// closes in the price history CSV, and trading on weekdays other than the
+/// holidays in `holidays.yaml`. Valuations in `psp.yaml` replace closes on the
+/// same date.
+pub fn load_psp(files: &PortfolioFiles) -> Result<model::psp::PreferredStockPrice> {
+    let (portfolio_path, strict) = (files.path(), files.strict);
+    let (valuations, history_path, holidays): (
+        Vec<model::psp::PreferredStockPriceValuation>,
+        Option<PathBuf>,
+        Vec<NaiveDate>,
+    ) = if let Some(portfolio) = &files.file {
+        let history_path = portfolio
+            .price_history
+            .as_ref()
+            .map(|history| price_history::history_path(portfolio_path, history));
+        (
+            portfolio.prices.iter().map(|p| p.to_model()).collect(),
+            history_path,
+            portfolio.holidays.iter().map(|h| h.0).collect(),
+        )
+    } else {
+        let history_path = Some(portfolio_path.join("psp.csv"));
+        let holidays = load_holidays(portfolio_path, strict)?
+            .into_iter()
+            .map(|h| h.0)
+            .collect();
+        let psp_path = portfolio_path.join("psp.yaml");
+        if !psp_path.exists() {
+            (vec![], history_path, holidays)
+        } else {
+            let contents = fs::read_to_string(&psp_path)
+                .map_err(|_| LoadError::FileLoadFailed(psp_path.clone()))?;
+
+            let result: Vec<PreferredStockPrice> =
+                deserialize_documents(&psp_path, &contents, strict).with_context(|| {
+                    format!(
+                        "Preferred Stock Price deserialize failed from {:?}",
+                        &psp_path
+                    )
+                })?;
+            (
+                result.iter().map(|p| p.to_model()).collect(),
+                history_path,
+                holidays,
+            )
+        }
+    };
+
+    let history = match history_path {
+        Some(path) if path.exists() => price_history::read_price_history(&path)?,
+        _ => vec![],
+    };
+
+    let calendar = model::trading::TradingCalendar::new(holidays);
+
+    Ok(model::psp::PreferredStockPrice::new(history)
+        .with_valuations(valuations)
+        .with_calendar(calendar))
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Scenario {
+    name: String,
+
+    /// Valuations added to, or replacing those in, `psp.yaml`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    valuations: Vec<PreferredStockPrice>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    growth: Option<ScenarioGrowth>,
+
+    /// When the company is acquired, triggering any single-trigger acceleration
+    #[serde(
+        default,
+        deserialize_with = "naive_date_format::deserialize_option",
+        serialize_with = "naive_date_format::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    acquired_on: Option<NaiveDate>,
+
+    /// When the holder leaves, triggering any double-trigger acceleration
+    #[serde(
+        default,
+        deserialize_with = "naive_date_format::deserialize_option",
+        serialize_with = "naive_date_format::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    terminated_on: Option<NaiveDate>,
+}
 
-        result.push(psp);
+#[derive(Debug, Deserialize, Serialize)]
+struct ScenarioGrowth {
+    /// Annual growth rate, e.g. 0.1 for 10%
+    annual_rate: f64,
+
+    #[serde(with = "naive_date_format")]
+    until: NaiveDate,
+
+    #[serde(default = "default_growth_every_months")]
+    every_months: NonZeroU32,
+}
+
+fn default_growth_every_months() -> NonZeroU32 {
+    const { NonZeroU32::new(12).unwrap() }
+}
+
+impl Scenario {
+    pub fn to_model(&self, psp: &model::psp::PreferredStockPrice) -> model::scenario::Scenario {
+        let mut psp = psp.with_valuations(self.valuations.iter().map(|v| v.to_model()).collect());
+
+        if let Some(growth) = &self.growth {
+            psp = psp.with_growth(growth.annual_rate, growth.until, growth.every_months);
+        }
+
+        model::scenario::Scenario::new(self.name.clone(), psp, self.acquired_on, self.terminated_on)
+    }
+}
+
+/// Load the scenarios in `scenarios.yaml`, each applied on top of `psp`
+pub fn load_scenarios(
+    files: &PortfolioFiles,
+    psp: &model::psp::PreferredStockPrice,
+) -> Result<Vec<model::scenario::Scenario>> {
+    if let Some(portfolio) = &files.file {
+        return Ok(portfolio
+            .scenarios
+            .iter()
+            .map(|s| s.to_model(psp))
+            .collect());
     }
 
-    let valuations = result.into_iter().map(|p| p.to_model()).collect();
+    let scenarios_path = files.path.join("scenarios.yaml");
+
+    let contents = fs::read_to_string(&scenarios_path)
+        .map_err(|_| LoadError::FileLoadFailed(scenarios_path.clone()))?;
 
-    Ok(model::psp::PreferredStockPrice::new(valuations))
+    let result: Vec<Scenario> = deserialize_documents(&scenarios_path, &contents, files.strict)
+        .with_context(|| format!("Scenario deserialize failed from {:?}", &scenarios_path))?;
+
+    Ok(result.iter().map(|s| s.to_model(psp)).collect())
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct CapTable {
+    common_shares: i64,
+
+    #[serde(default)]
+    option_pool: i64,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    preferred: Vec<PreferredSeries>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    rounds: Vec<FundingRound>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct FundingRound {
+    name: String,
+
+    #[serde(with = "naive_date_format")]
+    date: NaiveDate,
+    new_shares: i64,
+
+    /// Price per share, in dollars.
+    price: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct PreferredSeries {
+    name: String,
+    shares: i64,
+
+    /// Original issue price per share, in dollars.
+    issue_price: f64,
+
+    #[serde(default = "default_liquidation_multiple")]
+    liquidation_multiple: f64,
+
+    #[serde(default)]
+    participating: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    participation_cap: Option<f64>,
+
+    #[serde(default)]
+    seniority: i32,
+}
+
+fn default_liquidation_multiple() -> f64 {
+    1.0
+}
+
+impl CapTable {
+    pub fn to_model(&self) -> model::cap_table::CapTable {
+        model::cap_table::CapTable::new(
+            self.common_shares,
+            self.option_pool,
+            self.preferred
+                .iter()
+                .map(|s| model::cap_table::PreferredSeries {
+                    name: s.name.clone(),
+                    shares: s.shares,
+                    issue_price_cents: (s.issue_price * 100.0).round() as i64,
+                    liquidation_multiple: s.liquidation_multiple,
+                    participating: s.participating,
+                    participation_cap: s.participation_cap,
+                    seniority: s.seniority,
+                })
+                .collect(),
+            self.rounds
+                .iter()
+                .map(|r| model::cap_table::FundingRound {
+                    name: r.name.clone(),
+                    date: r.date,
+                    new_shares: r.new_shares,
+                    price_cents: (r.price * 100.0).round() as i32,
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Load the cap table, if the portfolio has one
+pub fn load_cap_table(files: &PortfolioFiles) -> Result<Option<model::cap_table::CapTable>> {
+    if let Some(portfolio) = &files.file {
+        return Ok(portfolio.cap_table.as_ref().map(|c| c.to_model()));
+    }
+
+    let cap_table_path = files.path.join("cap_table.yaml");
+    if !cap_table_path.exists() {
+        return Ok(None);
+    }
+
+    let contents = fs::read_to_string(&cap_table_path)
+        .map_err(|_| LoadError::FileLoadFailed(cap_table_path.clone()))?;
+
+    let cap_table: CapTable = deserialize_document(&cap_table_path, &contents, files.strict)
+        .with_context(|| format!("Cap table deserialize failed from {:?}", &cap_table_path))?;
+
+    Ok(Some(cap_table.to_model()))
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum LeavePolicy {
+    Toll,
+    Suspend,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Leave {
+    #[serde(with = "naive_date_format")]
+    starts_on: NaiveDate,
+
+    /// Last day of the leave
+    #[serde(with = "naive_date_format")]
+    ends_on: NaiveDate,
+    policy: LeavePolicy,
+}
+
+impl Leave {
+    /// Why the leave cannot be modelled, if it cannot
+    fn check(&self) -> std::result::Result<(), String> {
+        if self.ends_on < self.starts_on {
+            return Err(format!(
+                "leave ends on {}, before it starts on {}",
+                self.ends_on, self.starts_on
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn to_model(&self) -> model::leave::Leave {
+        model::leave::Leave::new(
+            self.starts_on,
+            self.ends_on,
+            match self.policy {
+                LeavePolicy::Toll => model::leave::LeavePolicy::Toll,
+                LeavePolicy::Suspend => model::leave::LeavePolicy::Suspend,
+            },
+        )
+    }
+}
+
+/// Load leaves of absence, earliest first
+pub fn load_leaves(files: &PortfolioFiles) -> Result<Vec<model::leave::Leave>> {
+    let loaded: Vec<Leave>;
+    let (leaves_path, leaves) = if let Some(portfolio) = &files.file {
+        (files.path.clone(), &portfolio.leaves)
+    } else {
+        let leaves_path = files.path.join("leaves.yaml");
+        if !leaves_path.exists() {
+            return Ok(vec![]);
+        }
+
+        let contents = fs::read_to_string(&leaves_path)
+            .map_err(|_| LoadError::FileLoadFailed(leaves_path.clone()))?;
+
+        loaded = deserialize_documents(&leaves_path, &contents, files.strict)
+            .with_context(|| format!("Leave deserialize failed from {:?}", &leaves_path))?;
+        (leaves_path, &loaded)
+    };
+
+    for leave in leaves {
+        leave
+            .check()
+            .map_err(|message| LoadError::InvalidValue(leaves_path.clone(), message))?;
+    }
+    let mut leaves: Vec<model::leave::Leave> = leaves.iter().map(|l| l.to_model()).collect();
+    leaves.sort_by_key(|leave| leave.starts_on);
+    Ok(leaves)
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum PartTimePolicy {
+    Defer,
+    Forfeit,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct PartTime {
+    #[serde(with = "naive_date_format")]
+    starts_on: NaiveDate,
+
+    /// Last day of the interval
+    #[serde(with = "naive_date_format")]
+    ends_on: NaiveDate,
+
+    /// Percentage of full time worked, e.g. 60
+    percentage: f64,
+    policy: PartTimePolicy,
+}
+
+impl PartTime {
+    /// Why the interval cannot be modelled, if it cannot
+    fn check(&self) -> std::result::Result<(), String> {
+        if self.ends_on < self.starts_on {
+            return Err(format!(
+                "part-time interval ends on {}, before it starts on {}",
+                self.ends_on, self.starts_on
+            ));
+        }
+        if !(self.percentage > 0.0 && self.percentage <= 100.0) {
+            return Err(format!(
+                "part-time percentage {} is not more than 0 and at most 100",
+                self.percentage
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn to_model(&self) -> model::part_time::PartTime {
+        model::part_time::PartTime::new(
+            self.starts_on,
+            self.ends_on,
+            self.percentage,
+            match self.policy {
+                PartTimePolicy::Defer => model::part_time::PartTimePolicy::Defer,
+                PartTimePolicy::Forfeit => model::part_time::PartTimePolicy::Forfeit,
+            },
+        )
+    }
+}
+
+/// Load part-time intervals, earliest first
+pub fn load_part_time(files: &PortfolioFiles) -> Result<Vec<model::part_time::PartTime>> {
+    let loaded: Vec<PartTime>;
+    let (part_time_path, intervals) = if let Some(portfolio) = &files.file {
+        (files.path.clone(), &portfolio.part_time)
+    } else {
+        let part_time_path = files.path.join("part_time.yaml");
+        if !part_time_path.exists() {
+            return Ok(vec![]);
+        }
+
+        let contents = fs::read_to_string(&part_time_path)
+            .map_err(|_| LoadError::FileLoadFailed(part_time_path.clone()))?;
+
+        loaded = deserialize_documents(&part_time_path, &contents, files.strict)
+            .with_context(|| format!("Part time deserialize failed from {:?}", &part_time_path))?;
+        (part_time_path, &loaded)
+    };
+
+    for interval in intervals {
+        interval
+            .check()
+            .map_err(|message| LoadError::InvalidValue(part_time_path.clone(), message))?;
+    }
+    let mut intervals: Vec<model::part_time::PartTime> =
+        intervals.iter().map(|p| p.to_model()).collect();
+    intervals.sort_by_key(|interval| interval.starts_on);
+    Ok(intervals)
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+enum AccelerationTrigger {
+    Single,
+    Double,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+struct Acceleration {
+    trigger: AccelerationTrigger,
+
+    /// Percentage of unvested shares that accelerate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    percent: Option<f64>,
+
+    /// Accelerate the shares that would vest in this many months instead
+    #[serde(skip_serializing_if = "Option::is_none")]
+    months: Option<u32>,
+}
+
+impl Acceleration {
+    pub fn to_model(&self) -> model::acceleration::Acceleration {
+        model::acceleration::Acceleration::new(
+            match self.trigger {
+                AccelerationTrigger::Single => model::acceleration::Trigger::Single,
+                AccelerationTrigger::Double => model::acceleration::Trigger::Double,
+            },
+            match self.months {
+                Some(months) => model::acceleration::AccelerationAmount::Months(months),
+                None => {
+                    model::acceleration::AccelerationAmount::Percent(self.percent.unwrap_or(100.0))
+                }
+            },
+        )
+    }
+}
+
+/// Vesting in installments every `every_months` until fully vested after
+/// `months`, as an alternative to listing every event. With a cliff, nothing
+/// vests until `cliff_months`, when everything vested so far vests at once.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+struct VestingRule {
+    months: u32,
+    every_months: u32,
+
+    #[serde(default, skip_serializing_if = "is_zero")]
+    cliff_months: u32,
+}
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+impl VestingRule {
+    /// Why the rule cannot vest a grant, if it cannot
+    fn check(&self) -> std::result::Result<(), String> {
+        if self.months == 0 || self.every_months == 0 {
+            return Err("vesting rule months must be positive".to_string());
+        }
+        if self.cliff_months > self.months {
+            return Err(format!(
+                "vesting rule cliff of {} months is after it fully vests at {} months",
+                self.cliff_months, self.months
+            ));
+        }
+        Ok(())
+    }
+
+    /// Months after vesting commences of each installment
+    fn installments(&self) -> Vec<u32> {
+        let every = self.every_months.max(1);
+        let mut installments = Vec::new();
+        let mut month = if self.cliff_months > 0 {
+            self.cliff_months
+        } else {
+            every
+        };
+        while month < self.months {
+            installments.push(month);
+            month += every;
+        }
+        installments.push(self.months);
+        installments
+    }
+
+    /// The vesting events of a grant of `total` shares or units. Each
+    /// installment vests the whole shares vested by then in proportion to
+    /// time, so the last installment vests the remainder.
+    fn events(
+        &self,
+        commences_on: NaiveDate,
+        total: i32,
+    ) -> std::result::Result<Vec<(NaiveDate, i32)>, String> {
+        self.check()?;
+
+        let months = self.months as i64;
+        let mut vested = 0;
+        self.installments()
+            .into_iter()
+            .map(|month| {
+                let vested_by = (total as i64 * month as i64 / months) as i32;
+                let number = vested_by - vested;
+                vested = vested_by;
+                let date = commences_on
+                    .checked_add_months(Months::new(month))
+                    .ok_or_else(|| {
+                        format!(
+                            "vesting {} months after {} is past the last date",
+                            month, commences_on
+                        )
+                    })?;
+                Ok((date, number))
+            })
+            .collect()
+    }
+}
+
+impl OptionGrantVestingSchedule {
+    /// Date and number of shares of each vesting event, from the rule if
+    /// there is one
+    fn events(&self, shares: i32) -> std::result::Result<Vec<(NaiveDate, i32)>, String> {
+        match &self.rule {
+            Some(rule) => rule.events(self.commences_on, shares),
+            None => Ok(self
+                .events
+                .iter()
+                .map(|e| (e.date, e.number_of_shares))
+                .collect()),
+        }
+    }
+}
+
+/// The model of each item in `items`, read from `path`, or why the first that
+/// cannot be modelled cannot be
+fn to_models<T, M>(
+    path: &Path,
+    items: &[T],
+    to_model: impl Fn(&T) -> std::result::Result<M, String>,
+) -> std::result::Result<Vec<M>, LoadError> {
+    items
+        .iter()
+        .map(|item| {
+            to_model(item).map_err(|message| LoadError::InvalidValue(path.to_path_buf(), message))
+        })
+        .collect()
 }
 
 impl OptionGrant {
-    pub fn to_model(&self) -> model::option::OptionGrant {
+    /// The grant, or why it cannot be modelled
+    pub fn to_model(&self) -> std::result::Result<model::option::OptionGrant, String> {
         let vesting_events = self
             .vesting_schedule
-            .events
-            .iter()
-            .map(|e| model::option::OptionGrantVestingEvent::new(e.date, e.number_of_shares))
+            .events(self.grant_value.shares)
+            .map_err(|message| format!("{}: {}", self.name, message))?
+            .into_iter()
+            .map(|(date, number)| model::option::OptionGrantVestingEvent::new(date, number))
             .collect();
+        let expires_on = match self.expires_on {
+            Some(expires_on) => expires_on,
+            None => self
+                .date
+                .checked_add_months(Months::new(120))
+                .ok_or_else(|| {
+                    format!(
+                        "{}: expiry 10 years after {} is past the last date",
+                        self.name, self.date
+                    )
+                })?,
+        };
 
-        model::option::OptionGrant::new(
+        Ok(model::option::OptionGrant::new(
             self.name.clone(),
             self.date,
             model::option::OptionGrantValue::new(
-                (self.grant_value.exercise_price * 100.0) as i32,
+                (self.grant_value.exercise_price * 100.0).round() as i32,
                 self.grant_value.shares,
             ),
             model::option::OptionGrantVestingSchedule::new(
                 self.vesting_schedule.commences_on,
                 vesting_events,
             ),
+            self.post_termination_exercise_period,
+            expires_on,
+            self.acceleration.as_ref().map(|a| a.to_model()),
         )
+        .with_vest_price(self.vest_price))
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 struct OptionGrant {
     name: String,
 
     #[serde(with = "naive_date_format")]
+    #[schemars(with = "NaiveDate")]
     date: NaiveDate,
     grant_value: OptionGrantValue,
     vesting_schedule: OptionGrantVestingSchedule,
+
+    /// How long vested options can be exercised for after leaving, e.g. 90d or 7y
+    #[serde(
+        default,
+        with = "exercise_period_format",
+        skip_serializing_if = "exercise_period_format::is_default"
+    )]
+    #[schemars(with = "String", regex(pattern = r"^\d+[dmy]$"))]
+    post_termination_exercise_period: model::option::ExercisePeriod,
+
+    /// Defaults to 10 years after the grant date
+    #[serde(
+        default,
+        deserialize_with = "naive_date_format::deserialize_option",
+        serialize_with = "naive_date_format::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<NaiveDate>")]
+    expires_on: Option<NaiveDate>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    acceleration: Option<Acceleration>,
+
+    /// Price vesting shares are valued at: close, prior_close or average_5d
+    #[serde(
+        default,
+        with = "vest_price_format",
+        skip_serializing_if = "vest_price_format::is_default"
+    )]
+    #[schemars(
+        with = "String",
+        regex(pattern = r"^(close|prior_close|average_\d+d)$")
+    )]
+    vest_price: model::trading::VestPriceRule,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 struct OptionGrantValue {
-    exercise_price: f32,
+    exercise_price: f64,
     shares: i32,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 struct OptionGrantVestingSchedule {
     #[serde(with = "naive_date_format")]
+    #[schemars(with = "NaiveDate")]
     commences_on: NaiveDate,
+
+    /// Installments to vest the grant in, instead of listing `events`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rule: Option<VestingRule>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     events: Vec<OptionGrantVestingEvent>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 struct OptionGrantVestingEvent {
     #[serde(with = "naive_date_format")]
+    #[schemars(with = "NaiveDate")]
     date: NaiveDate,
     number_of_shares: i32,
 }
 
-pub fn load_option_grants(portfolio_path: &Path) -> Result<Vec<model::option::OptionGrant>> {
-    let grants_path = portfolio_path.join("option_grants.yaml");
+/// The option grants of the portfolio, or none without an options file
+pub fn load_option_grants(files: &PortfolioFiles) -> Result<Vec<model::option::OptionGrant>> {
+    if let Some(portfolio) = &files.file {
+        return Ok(to_models(
+            &files.path,
+            &portfolio.options,
+            OptionGrant::to_model,
+        )?);
+    }
+
+    let grants_path = files.path.join("option_grants.yaml");
+    if !grants_path.exists() {
+        return Ok(vec![]);
+    }
     let contents = fs::read_to_string(&grants_path)
         .map_err(|_| LoadError::FileLoadFailed(grants_path.clone()))?;
 
-    let mut result: Vec<OptionGrant> = Vec::new();
-    for doc in Deserializer::from_str(&contents) {
-        let grant = OptionGrant::deserialize(doc)
-            .map_err(LoadError::DeserializationFailed)
-            .with_context(|| format!("Options Grant deserialize failed from {:?}", &grants_path))?;
-        result.push(grant);
-    }
+    let result: Vec<OptionGrant> = deserialize_documents(&grants_path, &contents, files.strict)
+        .with_context(|| format!("Options Grant deserialize failed from {:?}", &grants_path))?;
 
-    Ok(result.into_iter().map(|g| g.to_model()).collect())
+    Ok(to_models(&grants_path, &result, OptionGrant::to_model)?)
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 struct RestrictedStockUnitGrant {
     name: String,
 
     #[serde(with = "naive_date_format")]
+    #[schemars(with = "NaiveDate")]
     date: NaiveDate,
     grant_value: RestrictedStockUnitGrantValue,
     vesting_schedule: RestrictedStockUnitVestingSchedule,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    acceleration: Option<Acceleration>,
+
+    /// Price vesting shares are valued at: close, prior_close or average_5d
+    #[serde(
+        default,
+        with = "vest_price_format",
+        skip_serializing_if = "vest_price_format::is_default"
+    )]
+    #[schemars(
+        with = "String",
+        regex(pattern = r"^(close|prior_close|average_\d+d)$")
+    )]
+    vest_price: model::trading::VestPriceRule,
+}
+
+impl RestrictedStockUnitGrantValue {
+    pub fn to_model(&self) -> model::rsu::RestrictedStockUnitValue {
+        model::rsu::RestrictedStockUnitValue::new(
+            (self.grant_price * 100.0).round() as i32,
+            (self.total_value * 100.0).round() as i32,
+        )
+    }
 }
 
 impl RestrictedStockUnitGrant {
-    pub fn to_model(&self) -> model::rsu::RestrictedStockUnitGrant {
+    /// The grant, or why it cannot be modelled
+    pub fn to_model(&self) -> std::result::Result<model::rsu::RestrictedStockUnitGrant, String> {
+        let value = self.grant_value.to_model();
         let events = self
             .vesting_schedule
-            .events
-            .iter()
-            .map(|e| model::rsu::RestrictedStockUnitVestingEvent::new(e.date, e.number))
+            .events(value.units())
+            .map_err(|message| format!("{}: {}", self.name, message))?
+            .into_iter()
+            .map(|(date, number)| model::rsu::RestrictedStockUnitVestingEvent::new(date, number))
             .collect();
 
-        model::rsu::RestrictedStockUnitGrant::new(
+        Ok(model::rsu::RestrictedStockUnitGrant::new(
             self.name.clone(),
             self.date,
-            model::rsu::RestrictedStockUnitValue::new(
-                (self.grant_value.grant_price * 100.0) as i32,
-                (self.grant_value.total_value * 100.0) as i32,
-            ),
+            value,
             model::rsu::RestrictedStockUnitVestingSchedule::new(
                 self.vesting_schedule.commences_on,
                 events,
             ),
+            self.acceleration.as_ref().map(|a| a.to_model()),
         )
+        .with_vest_price(self.vest_price))
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 struct RestrictedStockUnitGrantValue {
     /// Unit price of each RSU in the grant, in dollars.
-    grant_price: f32,
+    grant_price: f64,
 
     /// Total value of the grant, in dollars.
-    total_value: f32,
+    total_value: f64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 struct RestrictedStockUnitVestingSchedule {
     #[serde(with = "naive_date_format")]
+    #[schemars(with = "NaiveDate")]
     commences_on: NaiveDate,
+
+    /// Installments to vest the grant in, instead of listing `events`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rule: Option<VestingRule>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     events: Vec<RestrictedStockUnitVestingEvent>,
 }
 
-#[derive(Debug, Deserialize)]
+impl RestrictedStockUnitVestingSchedule {
+    /// Date and number of units of each vesting event, from the rule if
+    /// there is one
+    fn events(&self, units: i32) -> std::result::Result<Vec<(NaiveDate, i32)>, String> {
+        match &self.rule {
+            Some(rule) => rule.events(self.commences_on, units),
+            None => Ok(self.events.iter().map(|e| (e.date, e.number)).collect()),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 struct RestrictedStockUnitVestingEvent {
     #[serde(with = "naive_date_format")]
+    #[schemars(with = "NaiveDate")]
     date: NaiveDate,
     number: i32,
 }
 
-pub fn load_rsu_grants(portfolio_path: &Path) -> Result<Vec<model::rsu::RestrictedStockUnitGrant>> {
-    let grants_path = portfolio_path.join("rsu_grants.yaml");
+/// The RSU grants of the portfolio, or none without an RSUs file
+pub fn load_rsu_grants(
+    files: &PortfolioFiles,
+) -> Result<Vec<model::rsu::RestrictedStockUnitGrant>> {
+    if let Some(portfolio) = &files.file {
+        return Ok(to_models(
+            &files.path,
+            &portfolio.rsus,
+            RestrictedStockUnitGrant::to_model,
+        )?);
+    }
+
+    let grants_path = files.path.join("rsu_grants.yaml");
+    if !grants_path.exists() {
+        return Ok(vec![]);
+    }
 
     let contents = fs::read_to_string(&grants_path)
         .map_err(|_| LoadError::FileLoadFailed(grants_path.clone()))?;
 
-    let mut result: Vec<RestrictedStockUnitGrant> = Vec::new();
-    for doc in Deserializer::from_str(&contents) {
-        let grant = RestrictedStockUnitGrant::deserialize(doc)
-            .map_err(LoadError::DeserializationFailed)
-            .with_context(|| {
+    let result: Vec<RestrictedStockUnitGrant> =
+        deserialize_documents(&grants_path, &contents, files.strict).with_context(|| {
+            format!(
+                "Restricted Stock Unit Grant deserialize failed from {:?}",
+                &grants_path
+            )
+        })?;
+
+    Ok(to_models(
+        &grants_path,
+        &result,
+        RestrictedStockUnitGrant::to_model,
+    )?)
+}
+
+/// A whole portfolio in a single YAML or TOML file, as an alternative to a
+/// directory of files. Missing sections are empty.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+struct Portfolio {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    prices: Vec<PreferredStockPrice>,
+
+    /// CSV of daily closes merged into `prices`, relative to the portfolio file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    price_history: Option<String>,
+
+    /// Days other than weekends without a close, as in `holidays.yaml`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    holidays: Vec<Holiday>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    options: Vec<OptionGrant>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    rsus: Vec<RestrictedStockUnitGrant>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    scenarios: Vec<Scenario>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cap_table: Option<CapTable>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    leaves: Vec<Leave>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    part_time: Vec<PartTime>,
+}
+
+/// A TOML value as the equivalent YAML value. TOML dates and times become
+/// strings, as they are written in YAML.
+fn toml_to_yaml(value: toml::Value) -> serde_yaml::Value {
+    match value {
+        toml::Value::String(s) => serde_yaml::Value::String(s),
+        toml::Value::Integer(i) => serde_yaml::Value::Number(i.into()),
+        toml::Value::Float(f) => serde_yaml::Value::Number(f.into()),
+        toml::Value::Boolean(b) => serde_yaml::Value::Bool(b),
+        toml::Value::Datetime(d) => serde_yaml::Value::String(d.to_string()),
+        toml::Value::Array(a) => {
+            serde_yaml::Value::Sequence(a.into_iter().map(toml_to_yaml).collect())
+        }
+        toml::Value::Table(t) => serde_yaml::Value::Mapping(
+            t.into_iter()
+                .map(|(k, v)| (serde_yaml::Value::String(k), toml_to_yaml(v)))
+                .collect(),
+        ),
+    }
+}
+
+fn is_toml(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "toml")
+}
+
+/// Deserialize a TOML portfolio. TOML errors carry their own location;
+/// errors in the structure of the portfolio only say where in the portfolio
+/// they are.
+fn deserialize_toml(
+    path: &Path,
+    contents: &str,
+    strict: bool,
+) -> std::result::Result<Portfolio, LoadError> {
+    let value: toml::Value = toml::from_str(contents)
+        .map_err(|e| LoadError::InvalidToml(path.to_path_buf(), e.to_string()))?;
+
+    let mut unknown_keys: Vec<String> = Vec::new();
+    let portfolio: Portfolio = serde_path_to_error::deserialize(serde_ignored::Deserializer::new(
+        toml_to_yaml(value),
+        &mut |key_path| unknown_keys.push(key_path.to_string()),
+    ))
+    .map_err(|e| LoadError::InvalidToml(path.to_path_buf(), e.to_string()))?;
+
+    match unknown_keys.first() {
+        Some(key_path) if strict => Err(LoadError::InvalidToml(
+            path.to_path_buf(),
+            format!("unknown field `{}`", key_path),
+        )),
+        _ => Ok(portfolio),
+    }
+}
+
+fn load_portfolio_file(path: &Path, strict: bool) -> Result<Portfolio> {
+    let contents =
+        fs::read_to_string(path).map_err(|_| LoadError::FileLoadFailed(path.to_path_buf()))?;
+
+    let portfolio = if is_toml(path) {
+        deserialize_toml(path, &contents, strict)
+    } else {
+        deserialize_document(path, &contents, strict)
+    };
+
+    portfolio.with_context(|| format!("Portfolio deserialize failed from {:?}", path))
+}
+
+/// A portfolio file with a published JSON Schema
+pub enum SchemaFile {
+    /// The preferred stock price file, psp.yaml
+    Psp,
+
+    /// The option grants file, option_grants.yaml
+    OptionGrants,
+
+    /// The RSU grants file, rsu_grants.yaml
+    RsuGrants,
+}
+
+/// JSON Schema for each document in `file`
+pub fn json_schema(file: &SchemaFile) -> schemars::Schema {
+    match file {
+        SchemaFile::Psp => schemars::schema_for!(PreferredStockPrice),
+        SchemaFile::OptionGrants => schemars::schema_for!(OptionGrant),
+        SchemaFile::RsuGrants => schemars::schema_for!(RestrictedStockUnitGrant),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PeriodBoundary(#[serde(with = "naive_date_format")] NaiveDate);
+
+/// Load a YAML list of dates marking the boundaries between report periods.
+/// When `strict`, keys that do not match any field are an error rather than
+/// ignored.
+pub fn load_period_boundaries(path: &Path, strict: bool) -> Result<Vec<NaiveDate>> {
+    let contents =
+        fs::read_to_string(path).map_err(|_| LoadError::FileLoadFailed(path.to_path_buf()))?;
+
+    let boundaries: Vec<PeriodBoundary> = deserialize_document(path, &contents, strict)
+        .with_context(|| format!("Period boundaries deserialize failed from {:?}", path))?;
+
+    Ok(boundaries.into_iter().map(|b| b.0).collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_exercise_period() {
+        use model::option::ExercisePeriod;
+
+        assert_eq!(
+            Some(ExercisePeriod::Days(90)),
+            exercise_period_format::parse("90d")
+        );
+        assert_eq!(
+            Some(ExercisePeriod::Months(6)),
+            exercise_period_format::parse("6m")
+        );
+        assert_eq!(
+            Some(ExercisePeriod::Months(84)),
+            exercise_period_format::parse("7y")
+        );
+        assert_eq!(None, exercise_period_format::parse("7 years"));
+        assert_eq!(None, exercise_period_format::parse(""));
+        assert_eq!(None, exercise_period_format::parse("999999999y"));
+
+        assert_eq!(
+            "90d",
+            exercise_period_format::format(&ExercisePeriod::Days(90))
+        );
+        assert_eq!(
+            "6m",
+            exercise_period_format::format(&ExercisePeriod::Months(6))
+        );
+        assert_eq!(
+            "7y",
+            exercise_period_format::format(&ExercisePeriod::Months(84))
+        );
+    }
+
+    #[test]
+    fn test_strict_rejects_unknown_keys() {
+        let path = Path::new("psp.yaml");
+        let contents =
+            "date: 2024-01-01\nprice: 1.00\n---\ndate: 2024-06-01\nprise: 2.00\nprice: 2.00\n";
+
+        assert_eq!(
+            2,
+            deserialize_documents::<PreferredStockPrice>(path, contents, false)
+                .unwrap()
+                .len()
+        );
+
+        let error = deserialize_documents::<PreferredStockPrice>(path, contents, true)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("prise"), "{}", error);
+        assert!(error.contains("psp.yaml:5"), "{}", error);
+
+        let nested = "date: 2024-01-01\nprice: 1.00\n---\ndate: 2024-06-01\nprice: 2.00\n";
+        let grants = format!(
+            "{}---\n{}",
+            "name: A\ndate: 2024-01-01\ngrant_value:\n  exercise_price: 1.00\n  shares: 100\nvesting_schedule:\n  commences_on: 2024-01-01\n  events: []\n",
+            "name: B\ndate: 2024-01-01\ngrant_value:\n  exercise_price: 1.00\n  shares: 100\nvesting_schedule:\n  commences_on: 2024-01-01\n  events: []\n  date: 2024-01-01\n",
+        );
+        assert!(deserialize_documents::<PreferredStockPrice>(path, nested, true).is_ok());
+        let error =
+            deserialize_documents::<OptionGrant>(Path::new("option_grants.yaml"), &grants, true)
+                .unwrap_err()
+                .to_string();
+        assert!(error.contains("vesting_schedule.date"), "{}", error);
+        assert!(error.contains("option_grants.yaml:18"), "{}", error);
+
+        let toml = "[[prices]]\ndate = 2024-01-01\nprice = 1.0\nprise = 1.0\n";
+        assert!(deserialize_toml(Path::new("portfolio.toml"), toml, false).is_ok());
+        assert!(deserialize_toml(Path::new("portfolio.toml"), toml, true).is_err());
+    }
+
+    #[test]
+    fn test_yaml_documents() {
+        let path = Path::new("psp.yaml");
+        let contents = "%YAML 1.2\n---\ndate: 2024-01-01\nprice: 1.00\n...\n# no price yet\n---\n--- {date: 2024-06-01, price: 2.00}\n";
+
+        let psp: Vec<PreferredStockPrice> = deserialize_documents(path, contents, true).unwrap();
+        assert_eq!(2, psp.len());
+        assert_eq!(200, psp[1].to_model().value_cents());
+
+        // serde_yaml repeats a syntax error for every later document
+        let contents =
+            "date: 2024-01-01\nprice: 1.00\n---\ndate: 2024-06-01\nprice: 2.00: x\n---\ndate: 2024-07-01\nprice: 3.00\n";
+        let errors: Vec<_> = deserialize_each::<PreferredStockPrice>(path, contents)
+            .into_iter()
+            .filter_map(|parsed| parsed.err())
+            .collect();
+        assert_eq!(1, errors.len());
+
+        let contents = "date: 2024-01-01\nprice: 1.00\n---\ndate: 2024-06-01\nprice: two\n";
+        let error = deserialize_documents::<PreferredStockPrice>(path, contents, false)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("psp.yaml:5"), "{}", error);
+    }
+
+    #[test]
+    fn test_prices_round_to_cents() {
+        // Truncating 1.13 * 100.0 = 112.99999999999999 would lose a cent
+        let psp: Vec<PreferredStockPrice> = deserialize_documents(
+            Path::new("psp.yaml"),
+            "date: 2024-01-01\nprice: 1.13\n",
+            true,
+        )
+        .unwrap();
+        assert_eq!(113, psp[0].to_model().value_cents());
+
+        let options: Vec<OptionGrant> = deserialize_documents(
+            Path::new("option_grants.yaml"),
+            "name: Options\ndate: 2024-01-01\ngrant_value:\n  exercise_price: 0.29\n  shares: 100\nvesting_schedule:\n  commences_on: 2024-01-01\n  events: []\n",
+            true,
+        )
+        .unwrap();
+        assert_eq!(29, options[0].to_model().unwrap().value.exercise_price);
+
+        let rsus: Vec<RestrictedStockUnitGrant> = deserialize_documents(
+            Path::new("rsu_grants.yaml"),
+            "name: RSUs\ndate: 2024-01-01\ngrant_value:\n  grant_price: 1.13\n  total_value: 1.15\nvesting_schedule:\n  commences_on: 2024-01-01\n  events: []\n",
+            true,
+        )
+        .unwrap();
+        let value = rsus[0].to_model().unwrap().value;
+        assert_eq!(
+            (113, 115),
+            (value.grant_price_cents, value.total_value_cents)
+        );
+    }
+
+    #[test]
+    fn test_json_schema() {
+        let schema = serde_json::to_value(json_schema(&SchemaFile::OptionGrants)).unwrap();
+
+        assert_eq!("OptionGrant", schema["title"]);
+        assert_eq!(false, schema["additionalProperties"]);
+        for field in ["name", "date", "grant_value", "vesting_schedule"] {
+            assert!(
+                schema["required"]
+                    .as_array()
+                    .unwrap()
+                    .contains(&field.into()),
+                "{} is not required",
+                field
+            );
+        }
+        assert_eq!(
+            false,
+            schema["$defs"]["OptionGrantVestingEvent"]["additionalProperties"]
+        );
+    }
+
+    #[test]
+    fn test_scenario_growth_every_months() {
+        let path = Path::new("scenarios.yaml");
+        let growth = |every_months: &str| {
+            deserialize_documents::<Scenario>(
+                path,
+                &format!(
+                    "name: Grow\ngrowth:\n  annual_rate: 0.1\n  until: 2030-01-01\n{}",
+                    every_months
+                ),
+                true,
+            )
+        };
+
+        let scenarios = growth("").unwrap();
+        assert_eq!(12, scenarios[0].growth.as_ref().unwrap().every_months.get());
+        assert!(growth("  every_months: 0\n").is_err());
+    }
+
+    #[test]
+    fn test_inverted_leave() {
+        let dir = tempfile::tempdir().unwrap();
+        let leaves = "starts_on: 2024-03-01\nends_on: 2024-03-31\npolicy: toll\n";
+        fs::write(dir.path().join("leaves.yaml"), leaves).unwrap();
+
+        let files = PortfolioFiles::open(dir.path(), true).unwrap();
+        assert_eq!(Some(31), load_leaves(&files).unwrap()[0].days());
+
+        fs::write(
+            dir.path().join("leaves.yaml"),
+            leaves.replace("2024-03-31", "2024-02-29"),
+        )
+        .unwrap();
+        let error = load_leaves(&files).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<LoadError>(),
+            Some(LoadError::InvalidValue(..))
+        ));
+    }
+
+    #[test]
+    fn test_part_time_percentage() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = PortfolioFiles::open(dir.path(), true).unwrap();
+        let part_time = |percentage: &str| {
+            fs::write(
+                dir.path().join("part_time.yaml"),
                 format!(
-                    "Restricted Stock Unit Grant deserialize failed from {:?}",
-                    &grants_path
-                )
-            })?;
-        result.push(grant);
+                    "starts_on: 2024-01-01\nends_on: 2024-06-30\npercentage: {}\npolicy: defer\n",
+                    percentage
+                ),
+            )
+            .unwrap();
+            load_part_time(&files)
+        };
+
+        assert_eq!(100.0, part_time("100").unwrap()[0].percentage);
+        assert_eq!(0.5, part_time("0.5").unwrap()[0].percentage);
+        for percentage in ["0", "-50", "250", ".nan"] {
+            let error = part_time(percentage).unwrap_err();
+            assert!(
+                matches!(
+                    error.downcast_ref::<LoadError>(),
+                    Some(LoadError::InvalidValue(..))
+                ),
+                "{}",
+                percentage
+            );
+        }
+    }
+
+    #[test]
+    fn test_vesting_rule() {
+        let commences_on = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let rule = |months, every_months, cliff_months| VestingRule {
+            months,
+            every_months,
+            cliff_months,
+        };
+
+        let date = |y, m| NaiveDate::from_ymd_opt(y, m, 1).unwrap();
+        assert_eq!(
+            Ok(vec![
+                (date(2025, 1), 50),
+                (date(2025, 7), 25),
+                (date(2026, 1), 25)
+            ]),
+            rule(24, 6, 12).events(commences_on, 100)
+        );
+        assert_eq!(
+            Ok(vec![(date(2026, 1), 100)]),
+            rule(24, 6, 24).events(commences_on, 100)
+        );
+
+        // A cliff after the last installment would vest a negative remainder,
+        // and no months would vest nothing
+        assert!(rule(24, 6, 36).events(commences_on, 100).is_err());
+        assert!(rule(0, 1, 0).events(commences_on, 100).is_err());
+        assert!(rule(12, 0, 0).events(commences_on, 100).is_err());
+
+        assert!(rule(12, 12, 0)
+            .events(NaiveDate::MAX - chrono::Days::new(30), 100)
+            .is_err());
     }
 
-    Ok(result.into_iter().map(|g| g.to_model()).collect())
+    #[test]
+    fn test_invalid_rule() {
+        let dir = tempfile::tempdir().unwrap();
+        let grants = "name: Options\ndate: 2024-01-01\ngrant_value:\n  exercise_price: 1.00\n  shares: 100\nvesting_schedule:\n  commences_on: 2024-01-01\n  rule:\n    months: 12\n    every_months: 3\n";
+        fs::write(dir.path().join("option_grants.yaml"), grants).unwrap();
+
+        let files = PortfolioFiles::open(dir.path(), true).unwrap();
+        assert_eq!(
+            4,
+            load_option_grants(&files).unwrap()[0]
+                .vesting_schedule
+                .events
+                .len()
+        );
+
+        fs::write(
+            dir.path().join("option_grants.yaml"),
+            format!("{}    cliff_months: 13\n", grants),
+        )
+        .unwrap();
+        let error = load_option_grants(&files).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<LoadError>(),
+            Some(LoadError::InvalidValue(..))
+        ));
+    }
 }
@@ -0,0 +1,134 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use chrono::NaiveDate;
+
+use super::{
+    import::{parse_date, parse_number},
+    LoadError,
+};
+use crate::model;
+
+/// Headers of the closing price column, in order of preference
+const CLOSE_HEADERS: [&str; 4] = ["close", "close/last", "adj close", "price"];
+
+/// Closes written by some exports for days without trading
+fn is_missing(close: &str) -> bool {
+    matches!(
+        close.to_ascii_lowercase().as_str(),
+        "" | "null" | "-" | "n/a"
+    )
+}
+
+/// The price history CSV of a single-file portfolio, relative to the file
+pub(super) fn history_path(portfolio_path: &Path, price_history: &str) -> PathBuf {
+    portfolio_path
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(price_history)
+}
+
+/// Daily closing prices from a CSV of dates and closes, such as a market data
+/// export, in date order. The header row is optional; without one the first
+/// column is the date and the second the close. Rows without a close are
+/// skipped, and a date may only be repeated with the same close.
+pub(super) fn read_price_history(
+    path: &Path,
+) -> Result<Vec<model::psp::PreferredStockPriceValuation>, LoadError> {
+    let invalid =
+        |line: usize, message: String| LoadError::InvalidCsv(path.to_path_buf(), line, message);
+
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_path(path)
+        .map_err(|_| LoadError::FileLoadFailed(path.to_path_buf()))?;
+
+    let mut records = reader.records().peekable();
+    let (date_column, close_column) = match records.peek() {
+        Some(Ok(first)) if parse_date(first.get(0).unwrap_or("")).is_none() => {
+            let find = |names: &[&str]| {
+                names.iter().find_map(|name| {
+                    first
+                        .iter()
+                        .position(|header| header.trim().eq_ignore_ascii_case(name))
+                })
+            };
+            let columns = find(&["date"]).zip(find(&CLOSE_HEADERS));
+            let columns = columns.ok_or_else(|| {
+                invalid(1, "expected a date and a close or price column".to_string())
+            })?;
+            records.next();
+            columns
+        }
+        _ => (0, 1),
+    };
+
+    let mut closes: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+    for record in records {
+        let record = record.map_err(|e| invalid(0, e.to_string()))?;
+        let line = record
+            .position()
+            .map_or(0, |position| position.line() as usize);
+        let field = |column: usize| record.get(column).unwrap_or("").trim();
+
+        if field(date_column).is_empty() || is_missing(field(close_column)) {
+            continue;
+        }
+
+        let date = parse_date(field(date_column))
+            .ok_or_else(|| invalid(line, format!("invalid date {:?}", field(date_column))))?;
+        let close = parse_number(field(close_column))
+            .ok_or_else(|| invalid(line, format!("invalid close {:?}", field(close_column))))?;
+
+        match closes.insert(date, close) {
+            Some(existing) if existing != close => Err(invalid(
+                line,
+                format!("close {} on {} conflicts with {}", close, date, existing),
+            ))?,
+            _ => {}
+        }
+    }
+
+    Ok(closes
+        .into_iter()
+        .map(|(date, close)| {
+            model::psp::PreferredStockPriceValuation::new(date, (close * 100.0).round() as i32)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_read_price_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("psp.csv");
+        fs::write(
+            &path,
+            "Date,Close/Last,Volume\n\
+             01/05/2024,$10.25,100\n\
+             01/04/2024,$10.00,100\n\
+             01/04/2024,$10.00,100\n\
+             01/06/2024,,0\n",
+        )
+        .unwrap();
+
+        let history = read_price_history(&path).unwrap();
+
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(
+            vec![
+                model::psp::PreferredStockPriceValuation::new(date(2024, 1, 4), 1000),
+                model::psp::PreferredStockPriceValuation::new(date(2024, 1, 5), 1025),
+            ],
+            history
+        );
+    }
+}
@@ -0,0 +1,664 @@
+use std::{collections::HashMap, fmt, path::Path, path::PathBuf};
+
+/// One document of a multi-document YAML file
+#[derive(Clone)]
+pub struct Document<'a> {
+    /// 0-based position of the document in the file
+    pub index: usize,
+
+    /// 1-based line of the first line of the document
+    pub line: usize,
+    pub text: &'a str,
+}
+
+impl Document<'_> {
+    /// Line of the `n`th (0-based) line in the document setting `key`, in
+    /// YAML or TOML, ignoring indentation and sequence dashes
+    pub fn line_of(&self, key: &str, n: usize) -> usize {
+        self.line_after(0, key, n)
+    }
+
+    /// Line of the `n`th (0-based) vesting event
+    pub fn event_line(&self, n: usize) -> usize {
+        let events = self.line_of("events", 0) - self.line;
+        self.line_after(events, "date", n)
+    }
+
+    fn line_after(&self, skip: usize, key: &str, n: usize) -> usize {
+        self.text
+            .lines()
+            .enumerate()
+            .skip(skip)
+            .filter(|(_, line)| {
+                strip_dash(line).strip_prefix(key).is_some_and(|rest| {
+                    let rest = rest.trim_start();
+                    rest.starts_with(':') || rest.starts_with('=')
+                })
+            })
+            .nth(n)
+            .map(|(i, _)| self.line + i)
+            .unwrap_or(self.line)
+    }
+
+    /// Each mapping key in the document with its 1-based line and column
+    fn keys(&self) -> Vec<(&str, usize, usize)> {
+        self.text
+            .lines()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let stripped = strip_dash(line);
+                let key = &stripped[..stripped.find(':')?];
+                if !is_key(key) {
+                    return None;
+                }
+                Some((key, self.line + i, line.len() - stripped.len() + 1))
+            })
+            .collect()
+    }
+
+    /// Each mapping key in the document with its path from the root, as
+    /// serde_ignored writes it, e.g. `events.1.date`, and its 1-based line and
+    /// column. Block style only; keys in flow style are not found.
+    fn key_paths(&self) -> Vec<(String, usize, usize)> {
+        // Keys and sequence items enclosing the current line, with their
+        // indentation
+        let mut stack: Vec<(usize, String, bool)> = Vec::new();
+        let mut items: HashMap<String, usize> = HashMap::new();
+        let mut paths = Vec::new();
+
+        for (i, line) in self.text.lines().enumerate() {
+            let content = line.trim_start();
+            if content.is_empty() || content.starts_with('#') || is_separator(line).is_some() {
+                continue;
+            }
+
+            let mut indent = line.len() - content.len();
+            let mut rest = content;
+            while let Some(item) = rest
+                .strip_prefix('-')
+                .filter(|item| item.is_empty() || item.starts_with(' '))
+            {
+                // A sequence item, which may be at the same indentation as
+                // the key holding the sequence
+                while stack
+                    .last()
+                    .is_some_and(|(top, _, is_item)| *top > indent || (*top == indent && *is_item))
+                {
+                    stack.pop();
+                }
+                let parent = join(&stack);
+                let index = items.entry(parent.clone()).or_insert(0);
+                stack.push((indent, index.to_string(), true));
+                *index += 1;
+
+                let item_content = item.trim_start();
+                indent += rest.len() - item_content.len();
+                rest = item_content;
+            }
+
+            let Some(key) = rest
+                .find(':')
+                .map(|end| &rest[..end])
+                .filter(|key| is_key(key))
+            else {
+                continue;
+            };
+            while stack.last().is_some_and(|(top, _, _)| *top >= indent) {
+                stack.pop();
+            }
+            stack.push((indent, key.to_string(), false));
+            paths.push((join(&stack), self.line + i, indent + 1));
+        }
+
+        paths
+    }
+}
+
+/// The path of the innermost key on `stack`
+fn join(stack: &[(usize, String, bool)]) -> String {
+    stack
+        .iter()
+        .map(|(_, segment, _)| segment.as_str())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+fn is_key(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn strip_dash(line: &str) -> &str {
+    line.trim_start().trim_start_matches('-').trim_start()
+}
+
+/// If `line` is a document start (`---`) or end (`...`) marker, anything
+/// after the marker on the same line
+fn is_separator(line: &str) -> Option<&str> {
+    let rest = line
+        .strip_prefix("---")
+        .or_else(|| line.strip_prefix("..."))?;
+    (rest.trim().is_empty() || rest.starts_with([' ', '\t'])).then(|| rest.trim())
+}
+
+/// Lines that are not part of a document's content: blank lines, comments and
+/// directives such as `%YAML 1.2`
+fn is_content(line: &str) -> bool {
+    let trimmed = line.trim();
+    !trimmed.is_empty() && !trimmed.starts_with('#') && !line.starts_with('%')
+}
+
+/// Split `contents` into its documents, skipping any that are empty. This is
+/// only used to find where things are: serde_yaml decides what the documents
+/// are when deserializing.
+pub fn documents(contents: &str) -> Vec<Document<'_>> {
+    let mut documents = Vec::new();
+    let mut start = 0;
+    let mut start_line = 1;
+
+    let mut offset = 0;
+    for (i, line) in contents.split_inclusive('\n').enumerate() {
+        if let Some(rest) = is_separator(line) {
+            documents.push(Document {
+                index: 0,
+                line: start_line,
+                text: &contents[start..offset],
+            });
+            // Content on the marker line, such as `--- !tag` or
+            // `--- {date: 2024-01-01}`, starts the next document there
+            if rest.is_empty() || rest.starts_with('#') {
+                start = offset + line.len();
+                start_line = i + 2;
+            } else {
+                start = offset;
+                start_line = i + 1;
+            }
+        }
+        offset += line.len();
+    }
+    documents.push(Document {
+        index: 0,
+        line: start_line,
+        text: &contents[start..],
+    });
+
+    documents.retain(|document| {
+        document.text.lines().any(|line| {
+            is_content(line) && is_separator(line).is_none_or(|rest| !rest.starts_with('#'))
+        })
+    });
+    for (index, document) in documents.iter_mut().enumerate() {
+        document.index = index;
+    }
+    documents
+}
+
+/// The document of `documents` that `line` is in
+pub fn document_at<'a, 'b>(documents: &'b [Document<'a>], line: usize) -> Option<&'b Document<'a>> {
+    documents
+        .iter()
+        .take_while(|document| document.line <= line)
+        .last()
+}
+
+/// `contents` with the documents at the given indexes replaced and `added`
+/// appended. Everything else, including comments, is kept as it was, as are
+/// the comments leading a replaced document.
+pub fn splice_documents(contents: &str, replaced: &[(usize, String)], added: &[String]) -> String {
+    let mut spliced = String::new();
+    let mut copied = 0;
+
+    for document in documents(contents) {
+        let Some((_, text)) = replaced.iter().find(|(index, _)| *index == document.index) else {
+            continue;
+        };
+
+        // Documents are slices of `contents`
+        let mut start = document.text.as_ptr() as usize - contents.as_ptr() as usize;
+        for line in document.text.split_inclusive('\n') {
+            if !line.trim().is_empty() && !line.trim_start().starts_with('#') {
+                break;
+            }
+            start += line.len();
+        }
+
+        spliced.push_str(&contents[copied..start]);
+        spliced.push_str(text);
+        copied = document.text.as_ptr() as usize - contents.as_ptr() as usize + document.text.len();
+    }
+    spliced.push_str(&contents[copied..]);
+
+    for text in added {
+        if !documents(&spliced).is_empty() {
+            if !spliced.ends_with('\n') {
+                spliced.push('\n');
+            }
+            spliced.push_str("---\n");
+        }
+        spliced.push_str(text);
+    }
+
+    spliced
+}
+
+/// Each item in `section` of a single-file YAML or TOML portfolio
+pub fn section_items<'a>(contents: &'a str, section: &str) -> Vec<Document<'a>> {
+    let toml_header = format!("[[{}]]", section);
+    let toml_table = format!("[{}.", section);
+    let toml_array = format!("[[{}.", section);
+    let yaml_header = format!("{}:", section);
+
+    let mut starts: Vec<(usize, usize)> = Vec::new();
+    let mut end = contents.len();
+    let mut in_yaml_section = false;
+    let mut item_indent: Option<usize> = None;
+
+    let mut offset = 0;
+    for (i, line) in contents.split_inclusive('\n').enumerate() {
+        let trimmed = line.trim_end();
+        let indent = line.len() - line.trim_start().len();
+        let is_content = !trimmed.trim_start().is_empty() && !trimmed.trim_start().starts_with('#');
+
+        if trimmed == toml_header {
+            starts.push((i + 1, offset));
+        } else if trimmed.starts_with('[')
+            && !starts.is_empty()
+            && !trimmed.starts_with(&toml_table)
+            && !trimmed.starts_with(&toml_array)
+            && end == contents.len()
+        {
+            end = offset;
+        } else if trimmed == yaml_header {
+            in_yaml_section = true;
+        } else if in_yaml_section && is_content && indent == 0 && !trimmed.starts_with('-') {
+            in_yaml_section = false;
+            end = end.min(offset);
+        } else if in_yaml_section
+            && trimmed.trim_start().starts_with('-')
+            && item_indent.is_none_or(|item_indent| indent == item_indent)
+        {
+            item_indent = Some(indent);
+            starts.push((i + 1, offset));
+        }
+
+        offset += line.len();
+    }
+
+    starts
+        .iter()
+        .enumerate()
+        .map(|(index, (line, start))| {
+            let next = starts.get(index + 1).map_or(end, |(_, next)| *next);
+            Document {
+                index,
+                line: *line,
+                text: &contents[*start..next.max(*start)],
+            }
+        })
+        .collect()
+}
+
+/// The table `section` of a single-file YAML or TOML portfolio, such as
+/// `cap_table`, including any tables nested in it
+pub fn section<'a>(contents: &'a str, section: &str) -> Option<Document<'a>> {
+    let yaml_header = format!("{}:", section);
+    let toml_header = format!("[{}]", section);
+    let toml_table = format!("[{}.", section);
+    let toml_array = format!("[[{}.", section);
+
+    let mut start: Option<(usize, usize)> = None;
+    let mut end = contents.len();
+
+    let mut offset = 0;
+    for (i, line) in contents.split_inclusive('\n').enumerate() {
+        let trimmed = line.trim_end();
+        let is_yaml_key = trimmed
+            .find(':')
+            .is_some_and(|colon| is_key(&trimmed[..colon]));
+        let is_header = trimmed.starts_with('[') || is_yaml_key;
+        let is_section = trimmed == yaml_header
+            || trimmed.starts_with(&format!("{} ", yaml_header))
+            || trimmed == toml_header
+            || trimmed.starts_with(&toml_table)
+            || trimmed.starts_with(&toml_array);
+
+        if is_section && start.is_none() {
+            start = Some((i + 1, offset));
+        } else if is_header && !is_section && start.is_some() {
+            end = offset;
+            break;
+        }
+
+        offset += line.len();
+    }
+
+    start.map(|(line, start)| Document {
+        index: 0,
+        line,
+        text: &contents[start..end],
+    })
+}
+
+/// Number of single character edits to turn `a` into `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + if a == *b { 0 } else { 1 };
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// The candidate closest to `word`, if any is close enough to be a typo
+fn closest<'a>(word: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    candidates
+        .filter(|candidate| *candidate != word)
+        .map(|candidate| (edit_distance(word, candidate), candidate))
+        .filter(|(distance, candidate)| *distance <= 3.min(candidate.len() / 2))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// The text between each pair of backticks in a serde error message
+fn quoted(message: &str) -> Vec<&str> {
+    message.split('`').skip(1).step_by(2).collect()
+}
+
+/// A document that could not be deserialized, with enough context to find
+/// and fix the problem
+#[derive(Debug)]
+pub struct DocumentError {
+    path: PathBuf,
+
+    /// 0-based position of the document in the file
+    document: usize,
+
+    /// The `name` of the document, if it has one
+    name: Option<String>,
+    line: usize,
+    column: usize,
+    message: String,
+    snippet: Vec<(usize, String)>,
+    hint: Option<String>,
+}
+
+impl DocumentError {
+    pub fn new(path: &Path, document: &Document, error: &serde_yaml::Error) -> DocumentError {
+        // The error's own location is in the whole file
+        let message = error.to_string();
+        let message = match message.rfind(" at line ") {
+            Some(i) => message[..i].to_string(),
+            None => message,
+        };
+
+        let (mut line, mut column) = error
+            .location()
+            .map(|location| (location.line(), location.column()))
+            .unwrap_or((document.line, 1));
+
+        let keys = document.keys();
+        let mut hint = None;
+        if let Some(field) = message
+            .split("missing field `")
+            .nth(1)
+            .and_then(|rest| rest.split('`').next())
+        {
+            // A misspelled key leaves its field missing
+            let typo = keys
+                .iter()
+                .filter(|(_, key_line, _)| *key_line >= line)
+                .map(|(key, key_line, key_column)| {
+                    (edit_distance(key, field), *key, *key_line, *key_column)
+                })
+                .filter(|(distance, key, _, _)| {
+                    *key != field && *distance <= 3.min(field.len() / 2)
+                })
+                .min_by_key(|(distance, _, _, _)| *distance);
+            if let Some((_, key, key_line, key_column)) = typo {
+                hint = Some(format!("did you mean `{}` instead of `{}`?", field, key));
+                line = key_line;
+                column = key_column;
+            }
+        } else if message.contains("unknown field `") || message.contains("unknown variant `") {
+            let quoted = quoted(&message);
+            if let Some(suggestion) = quoted
+                .first()
+                .and_then(|unknown| closest(unknown, quoted[1..].iter().copied()))
+            {
+                hint = Some(format!("did you mean `{}`?", suggestion));
+            }
+        }
+
+        DocumentError::at(path, document, line, column, message, hint)
+    }
+
+    /// A key that does not match any field, where `key_path` is the path of
+    /// the key from the root of the document, as serde_ignored writes it
+    pub fn unknown_key(path: &Path, document: &Document, key_path: &str) -> DocumentError {
+        // serde_ignored marks each optional value on the path with `?`
+        let key_path = key_path
+            .split('.')
+            .filter(|segment| *segment != "?")
+            .collect::<Vec<_>>()
+            .join(".");
+        let (line, column) = document
+            .key_paths()
+            .into_iter()
+            .find(|(k, _, _)| *k == key_path)
+            .map(|(_, line, column)| (line, column))
+            .unwrap_or((document.line, 1));
+
+        DocumentError::at(
+            path,
+            document,
+            line,
+            column,
+            format!("unknown field `{}`", key_path),
+            None,
+        )
+    }
+
+    fn at(
+        path: &Path,
+        document: &Document,
+        line: usize,
+        column: usize,
+        message: String,
+        hint: Option<String>,
+    ) -> DocumentError {
+        let name = serde_yaml::from_str::<serde_yaml::Value>(document.text)
+            .ok()
+            .and_then(|value| value.get("name")?.as_str().map(|name| name.to_string()));
+
+        let snippet = document
+            .text
+            .lines()
+            .enumerate()
+            .map(|(i, text)| (document.line + i, text.to_string()))
+            .filter(|(number, _)| line.saturating_sub(1) <= *number && *number <= line)
+            .collect();
+
+        DocumentError {
+            path: path.to_path_buf(),
+            document: document.index,
+            name,
+            line,
+            column,
+            message,
+            snippet,
+            hint,
+        }
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The problem and any hint, on one line
+    pub fn summary(&self) -> String {
+        match &self.hint {
+            Some(hint) => format!("{} ({})", self.message, hint),
+            None => self.message.clone(),
+        }
+    }
+}
+
+impl fmt::Display for DocumentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.message)?;
+
+        write!(
+            f,
+            "  --> {}:{}:{} (document {}",
+            self.path.display(),
+            self.line,
+            self.column,
+            self.document + 1
+        )?;
+        if let Some(name) = &self.name {
+            write!(f, ", {:?}", name)?;
+        }
+        writeln!(f, ")")?;
+
+        let width = self.line.to_string().len();
+        writeln!(f, "{:width$} |", "")?;
+        for (number, text) in &self.snippet {
+            writeln!(f, "{:>width$} | {}", number, text)?;
+        }
+        write!(f, "{:width$} | {:>column$}", "", "^", column = self.column)?;
+
+        if let Some(hint) = &self.hint {
+            write!(f, "\n{:width$} = help: {}", "", hint)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_splice_documents() {
+        let contents = "# grants\na: 1\n---\n# b comes next\nb: 2\n# keep\n---\nc: 3";
+
+        assert_eq!(
+            "# grants\na: 1\n---\n# b comes next\nb: 20\n---\nc: 3\n---\nd: 4\n",
+            splice_documents(
+                contents,
+                &[(1, "b: 20\n".to_string())],
+                &["d: 4\n".to_string()]
+            )
+        );
+        assert_eq!(
+            "# no grants yet\na: 1\n",
+            splice_documents("# no grants yet\n", &[], &["a: 1\n".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_documents() {
+        let contents = "a: 1\n---\n# comment\n---\nb: 2\nc: 3\n";
+
+        let documents = documents(contents);
+
+        assert_eq!(2, documents.len());
+        assert_eq!(1, documents[0].line);
+        assert_eq!(5, documents[1].line);
+        assert_eq!(1, documents[1].index);
+        assert_eq!(6, documents[1].line_of("c", 0));
+
+        let contents = "%YAML 1.2\n---\na: 1\n...\n--- {b: 2}\n--- # comment\nc: 3\n";
+
+        let located = super::documents(contents);
+
+        assert_eq!(3, located.len());
+        assert_eq!(3, located[0].line);
+        assert_eq!(5, located[1].line);
+        assert_eq!(7, located[2].line);
+    }
+
+    #[test]
+    fn test_unknown_key_path() {
+        let contents = "a:\n  x: 1\nb:\n  x: 2\nevents:\n- x: 3\n  y: 4\n-   x: 5\n    y: 6\n";
+        let document = &documents(contents)[0];
+
+        let error = DocumentError::unknown_key(Path::new("a.yaml"), document, "b.x");
+        assert_eq!(4, error.line());
+
+        let error = DocumentError::unknown_key(Path::new("a.yaml"), document, "?.events.1.y");
+        assert_eq!(9, error.line());
+        assert!(
+            error.summary().contains("`events.1.y`"),
+            "{}",
+            error.summary()
+        );
+    }
+
+    #[test]
+    fn test_section_items() {
+        let yaml = "prices:\n  - date: 2024-01-01\n    price: 1\n  - date: 2024-02-01\n    price: 2\nrsus: []\n";
+        let items = section_items(yaml, "prices");
+        assert_eq!(2, items.len());
+        assert_eq!(4, items[1].line);
+        assert_eq!(5, items[1].line_of("price", 0));
+
+        let toml = "[[prices]]\ndate = 2024-01-01\nprice = 1\n\n[[prices]]\ndate = 2024-02-01\nprice = 2\n";
+        let items = section_items(toml, "prices");
+        assert_eq!(2, items.len());
+        assert_eq!(7, items[1].line_of("price", 0));
+    }
+
+    #[test]
+    fn test_section() {
+        let yaml =
+            "prices: []\ncap_table:\n  common_shares: 100\n  rounds:\n  - price: 1\nleaves: []\n";
+        let cap_table = section(yaml, "cap_table").unwrap();
+        assert_eq!(2, cap_table.line);
+        assert_eq!(5, cap_table.line_of("price", 0));
+        assert!(!cap_table.text.contains("leaves"));
+
+        let toml = "[[prices]]\nprice = 1\n\n[cap_table]\ncommon_shares = 100\n\n[[cap_table.rounds]]\nprice = 1\n\n[[leaves]]\n";
+        let cap_table = section(toml, "cap_table").unwrap();
+        assert_eq!(4, cap_table.line);
+        assert_eq!(8, cap_table.line_of("price", 0));
+        assert!(section(toml, "part_time").is_none());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(0, edit_distance("shares", "shares"));
+        assert_eq!(1, edit_distance("number_of_share", "number_of_shares"));
+        assert_eq!(2, edit_distance("exrcise_prce", "exercise_price"));
+        assert_eq!(3, edit_distance("kitten", "sitting"));
+    }
+
+    #[test]
+    fn test_missing_field_hint() {
+        #[derive(Debug, serde::Deserialize)]
+        #[allow(dead_code)]
+        struct Event {
+            date: String,
+            number_of_shares: i32,
+        }
+
+        let contents = "date: 2024-01-01\nnumber_of_share: 10\n";
+        let document = &documents(contents)[0];
+        let error = serde_yaml::from_str::<Event>(document.text).unwrap_err();
+
+        let error = DocumentError::new(Path::new("events.yaml"), document, &error);
+
+        assert_eq!(2, error.line);
+        assert_eq!(
+            Some("did you mean `number_of_shares` instead of `number_of_share`?".to_string()),
+            error.hint
+        );
+    }
+}
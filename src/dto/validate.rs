@@ -0,0 +1,1165 @@
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::NaiveDate;
+use serde::de::DeserializeOwned;
+
+use super::{
+    deserialize_document, deserialize_each, deserialize_toml, is_toml,
+    price_history::{history_path, read_price_history},
+    source::{section, section_items, Document, DocumentError},
+    CapTable, Leave, LoadError, OptionGrant, PartTime, Portfolio, PreferredStockPrice,
+    RestrictedStockUnitGrant, Scenario, VestingRule,
+};
+
+/// A problem found in a portfolio file
+pub struct Diagnostic {
+    /// The file the problem was found in
+    pub path: PathBuf,
+
+    /// 1-based line the problem was found on, when known
+    pub line: Option<usize>,
+
+    /// What is wrong, and the grant it concerns when there is one
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.path.display(), line, self.message),
+            None => write!(f, "{}: {}", self.path.display(), self.message),
+        }
+    }
+}
+
+/// Collects every problem found while validating a portfolio
+struct Validator {
+    /// Whether keys that do not match any field are reported
+    strict: bool,
+    diagnostics: Vec<Diagnostic>,
+
+    /// Where each grant name was first used, across the option and RSU files
+    grant_names: HashMap<String, (PathBuf, usize)>,
+}
+
+impl Validator {
+    fn new(strict: bool) -> Validator {
+        Validator {
+            strict,
+            diagnostics: Vec::new(),
+            grant_names: HashMap::new(),
+        }
+    }
+
+    fn report(&mut self, path: &Path, line: Option<usize>, message: String) {
+        self.diagnostics.push(Diagnostic {
+            path: path.to_path_buf(),
+            // Line 0 is used for documents that could not be located
+            line: line.filter(|line| *line > 0),
+            message,
+        });
+    }
+
+    /// Each document in the file that deserializes, with its document.
+    /// Documents that fail to deserialize are reported and skipped.
+    fn load<'a, T: DeserializeOwned>(
+        &mut self,
+        path: &Path,
+        contents: &'a str,
+    ) -> Vec<(Document<'a>, T)> {
+        let mut loaded = Vec::new();
+        for parsed in deserialize_each(path, contents) {
+            match parsed {
+                Ok(parsed) => {
+                    if self.strict {
+                        for key_path in &parsed.unknown_keys {
+                            let error =
+                                DocumentError::unknown_key(path, &parsed.document, key_path);
+                            self.report(path, Some(error.line()), error.summary());
+                        }
+                    }
+                    loaded.push((parsed.document, parsed.value));
+                }
+                Err(error) => self.report(path, Some(error.line()), error.summary()),
+            }
+        }
+        loaded
+    }
+
+    /// The single document in the file, or None if it fails to deserialize,
+    /// which is reported
+    fn load_one<T: DeserializeOwned>(&mut self, path: &Path, contents: &str) -> Option<T> {
+        match deserialize_document(path, contents, self.strict) {
+            Ok(value) => Some(value),
+            Err(LoadError::DeserializationFailed(e)) => {
+                self.report(path, Some(e.line()), e.summary());
+                None
+            }
+            Err(e) => {
+                self.report(path, None, e.to_string());
+                None
+            }
+        }
+    }
+
+    /// The contents of `path`, or None if it cannot be read. Every portfolio
+    /// file is optional.
+    fn read(&mut self, path: &Path) -> Option<String> {
+        if !path.exists() {
+            return None;
+        }
+
+        match fs::read_to_string(path) {
+            Ok(contents) => Some(contents),
+            Err(e) => {
+                self.report(path, None, format!("unable to read file: {}", e));
+                None
+            }
+        }
+    }
+
+    /// Grant names must be unique across both option and RSU grants
+    fn check_duplicate_names<'a>(
+        &mut self,
+        path: &Path,
+        names: impl Iterator<Item = (&'a str, usize)>,
+    ) {
+        for (name, line) in names {
+            let message = match self.grant_names.get(name) {
+                Some((first_path, first)) if first_path == path => format!(
+                    "duplicate grant name {:?}, first used on line {}",
+                    name, first
+                ),
+                Some((first_path, first)) => format!(
+                    "duplicate grant name {:?}, first used in {} on line {}",
+                    name,
+                    first_path.display(),
+                    first
+                ),
+                None => {
+                    self.grant_names
+                        .insert(name.to_string(), (path.to_path_buf(), line));
+                    continue;
+                }
+            };
+            self.report(path, Some(line), message);
+        }
+    }
+
+    /// Events must be on or after both the grant date and when vesting
+    /// commences
+    fn check_event_dates(
+        &mut self,
+        path: &Path,
+        document: &Document,
+        name: &str,
+        granted_on: NaiveDate,
+        commences_on: NaiveDate,
+        event_dates: impl Iterator<Item = NaiveDate>,
+    ) {
+        for (i, date) in event_dates.enumerate() {
+            let line = Some(document.event_line(i));
+            if date < commences_on {
+                self.report(
+                    path,
+                    line,
+                    format!(
+                        "{}: vesting event on {} is before vesting commences on {}",
+                        name, date, commences_on
+                    ),
+                );
+            }
+            if date < granted_on {
+                self.report(
+                    path,
+                    line,
+                    format!(
+                        "{}: vesting event on {} is before the grant date {}",
+                        name, date, granted_on
+                    ),
+                );
+            }
+        }
+    }
+
+    /// A rule must be able to produce `events`, and cannot be combined with
+    /// listed events. The events, when there are any.
+    fn check_rule(
+        &mut self,
+        path: &Path,
+        document: &Document,
+        name: &str,
+        rule: Option<&VestingRule>,
+        listed_events: bool,
+        events: Result<Vec<(NaiveDate, i32)>, String>,
+    ) -> Option<Vec<(NaiveDate, i32)>> {
+        let line = Some(document.line_of("rule", 0));
+        let events = match events {
+            Ok(events) => Some(events),
+            Err(message) => {
+                self.report(path, line, format!("{}: {}", name, message));
+                None
+            }
+        };
+        if rule.is_some() && listed_events {
+            self.report(
+                path,
+                line,
+                format!(
+                    "{}: vesting schedule has both a rule and events, the events are ignored",
+                    name
+                ),
+            );
+        }
+        events
+    }
+
+    /// Check the file at `path`, if there is one, as the portfolio file it is
+    /// named after
+    fn validate_file(&mut self, path: &Path) {
+        if let Some(contents) = self.read(path) {
+            self.check_file(path, &contents);
+        }
+    }
+
+    /// Check `contents`, read from `path`, as the portfolio file it is named
+    /// after
+    fn check_file(&mut self, path: &Path, contents: &str) {
+        match path.file_name().and_then(|name| name.to_str()) {
+            Some("psp.yaml") => {
+                let prices = self.load::<PreferredStockPrice>(path, contents);
+                self.check_psp(path, &prices);
+            }
+            Some("option_grants.yaml") => {
+                let grants = self.load::<OptionGrant>(path, contents);
+                self.check_option_grants(path, &grants);
+            }
+            Some("rsu_grants.yaml") => {
+                let grants = self.load::<RestrictedStockUnitGrant>(path, contents);
+                self.check_rsu_grants(path, &grants);
+            }
+            Some("scenarios.yaml") => {
+                let scenarios = self.load::<Scenario>(path, contents);
+                self.check_scenarios(path, &scenarios);
+            }
+            Some("cap_table.yaml") => {
+                if let Some(cap_table) = self.load_one::<CapTable>(path, contents) {
+                    let document = Document {
+                        index: 0,
+                        line: 1,
+                        text: contents,
+                    };
+                    self.check_cap_table(path, &document, &cap_table);
+                }
+            }
+            Some("leaves.yaml") => {
+                let leaves = self.load::<Leave>(path, contents);
+                self.check_leaves(path, &leaves);
+            }
+            Some("part_time.yaml") => {
+                let intervals = self.load::<PartTime>(path, contents);
+                self.check_part_time(path, &intervals);
+            }
+            _ => {}
+        }
+    }
+
+    fn check_psp(&mut self, path: &Path, prices: &[(Document, PreferredStockPrice)]) {
+        let mut previous: Option<NaiveDate> = None;
+        for (document, psp) in prices {
+            if psp.price <= 0.0 {
+                self.report(
+                    path,
+                    Some(document.line_of("price", 0)),
+                    format!("price {} on {} is not positive", psp.price, psp.date),
+                );
+            }
+
+            let line = Some(document.line_of("date", 0));
+            match previous {
+                Some(previous) if psp.date == previous => {
+                    self.report(path, line, format!("duplicate valuation on {}", psp.date))
+                }
+                Some(previous) if psp.date < previous => self.report(
+                    path,
+                    line,
+                    format!(
+                        "valuation on {} is out of order, after {}",
+                        psp.date, previous
+                    ),
+                ),
+                _ => {}
+            }
+            previous = Some(previous.map_or(psp.date, |previous| previous.max(psp.date)));
+        }
+    }
+
+    fn validate_price_history(&mut self, path: &Path) {
+        if !path.exists() {
+            return;
+        }
+
+        match read_price_history(path) {
+            Ok(_) => {}
+            Err(LoadError::InvalidCsv(path, line, message)) => {
+                self.report(&path, Some(line), message)
+            }
+            Err(e) => self.report(path, None, e.to_string()),
+        }
+    }
+
+    fn check_option_grants(&mut self, path: &Path, grants: &[(Document, OptionGrant)]) {
+        self.check_duplicate_names(
+            path,
+            grants
+                .iter()
+                .map(|(document, grant)| (grant.name.as_str(), document.line_of("name", 0))),
+        );
+
+        for (document, grant) in grants {
+            if grant.grant_value.exercise_price <= 0.0 {
+                self.report(
+                    path,
+                    Some(document.line_of("exercise_price", 0)),
+                    format!(
+                        "{}: exercise price {} is not positive",
+                        grant.name, grant.grant_value.exercise_price
+                    ),
+                );
+            }
+
+            let Some(events) = self.check_rule(
+                path,
+                document,
+                &grant.name,
+                grant.vesting_schedule.rule.as_ref(),
+                !grant.vesting_schedule.events.is_empty(),
+                grant.vesting_schedule.events(grant.grant_value.shares),
+            ) else {
+                continue;
+            };
+
+            let vesting: i32 = events.iter().map(|(_, number)| number).sum();
+            if vesting != grant.grant_value.shares {
+                self.report(
+                    path,
+                    Some(document.line_of("shares", 0)),
+                    format!(
+                        "{}: vesting events total {} shares but the grant is for {}",
+                        grant.name, vesting, grant.grant_value.shares
+                    ),
+                );
+            }
+
+            self.check_event_dates(
+                path,
+                document,
+                &grant.name,
+                grant.date,
+                grant.vesting_schedule.commences_on,
+                events.iter().map(|(date, _)| *date),
+            );
+        }
+    }
+
+    fn check_rsu_grants(&mut self, path: &Path, grants: &[(Document, RestrictedStockUnitGrant)]) {
+        self.check_duplicate_names(
+            path,
+            grants
+                .iter()
+                .map(|(document, grant)| (grant.name.as_str(), document.line_of("name", 0))),
+        );
+
+        for (document, grant) in grants {
+            let mut valid_value = true;
+            if grant.grant_value.grant_price <= 0.0 {
+                valid_value = false;
+                self.report(
+                    path,
+                    Some(document.line_of("grant_price", 0)),
+                    format!(
+                        "{}: grant price {} is not positive",
+                        grant.name, grant.grant_value.grant_price
+                    ),
+                );
+            }
+            if grant.grant_value.total_value <= 0.0 {
+                valid_value = false;
+                self.report(
+                    path,
+                    Some(document.line_of("total_value", 0)),
+                    format!(
+                        "{}: total value {} is not positive",
+                        grant.name, grant.grant_value.total_value
+                    ),
+                );
+            }
+
+            let units = grant.grant_value.to_model().units();
+            let Some(events) = self.check_rule(
+                path,
+                document,
+                &grant.name,
+                grant.vesting_schedule.rule.as_ref(),
+                !grant.vesting_schedule.events.is_empty(),
+                grant.vesting_schedule.events(units),
+            ) else {
+                continue;
+            };
+
+            let vesting: i32 = events.iter().map(|(_, number)| number).sum();
+            if valid_value && vesting != units {
+                self.report(
+                    path,
+                    Some(document.line_of("total_value", 0)),
+                    format!(
+                        "{}: vesting events total {} units but total value / grant price is {}",
+                        grant.name, vesting, units
+                    ),
+                );
+            }
+
+            self.check_event_dates(
+                path,
+                document,
+                &grant.name,
+                grant.date,
+                grant.vesting_schedule.commences_on,
+                events.iter().map(|(date, _)| *date),
+            );
+        }
+    }
+
+    fn check_scenarios(&mut self, path: &Path, scenarios: &[(Document, Scenario)]) {
+        let mut seen: HashMap<&str, usize> = HashMap::new();
+        for (document, scenario) in scenarios {
+            let line = document.line_of("name", 0);
+            match seen.get(scenario.name.as_str()) {
+                Some(first) => self.report(
+                    path,
+                    Some(line),
+                    format!(
+                        "duplicate scenario name {:?}, first used on line {}",
+                        scenario.name, first
+                    ),
+                ),
+                None => {
+                    seen.insert(&scenario.name, line);
+                }
+            }
+
+            for (i, valuation) in scenario.valuations.iter().enumerate() {
+                if valuation.price <= 0.0 {
+                    self.report(
+                        path,
+                        Some(document.line_of("price", i)),
+                        format!(
+                            "{}: price {} on {} is not positive",
+                            scenario.name, valuation.price, valuation.date
+                        ),
+                    );
+                }
+            }
+
+            if let Some(growth) = &scenario.growth {
+                if growth.annual_rate <= -1.0 {
+                    self.report(
+                        path,
+                        Some(document.line_of("annual_rate", 0)),
+                        format!(
+                            "{}: annual growth rate {} leaves no value",
+                            scenario.name, growth.annual_rate
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_cap_table(&mut self, path: &Path, document: &Document, cap_table: &CapTable) {
+        if cap_table.common_shares <= 0 {
+            self.report(
+                path,
+                Some(document.line_of("common_shares", 0)),
+                format!("common shares {} is not positive", cap_table.common_shares),
+            );
+        }
+        if cap_table.option_pool < 0 {
+            self.report(
+                path,
+                Some(document.line_of("option_pool", 0)),
+                format!("option pool {} is negative", cap_table.option_pool),
+            );
+        }
+
+        for (i, series) in cap_table.preferred.iter().enumerate() {
+            if series.shares <= 0 {
+                self.report(
+                    path,
+                    Some(document.line_of("shares", i)),
+                    format!("{}: shares {} is not positive", series.name, series.shares),
+                );
+            }
+            if series.issue_price <= 0.0 {
+                self.report(
+                    path,
+                    Some(document.line_of("issue_price", i)),
+                    format!(
+                        "{}: issue price {} is not positive",
+                        series.name, series.issue_price
+                    ),
+                );
+            }
+        }
+
+        let mut previous: Option<NaiveDate> = None;
+        for (i, round) in cap_table.rounds.iter().enumerate() {
+            if round.new_shares <= 0 {
+                self.report(
+                    path,
+                    Some(document.line_of("new_shares", i)),
+                    format!(
+                        "{}: new shares {} is not positive",
+                        round.name, round.new_shares
+                    ),
+                );
+            }
+            if round.price <= 0.0 {
+                self.report(
+                    path,
+                    Some(document.line_of("price", i)),
+                    format!("{}: price {} is not positive", round.name, round.price),
+                );
+            }
+            if let Some(previous) = previous.filter(|previous| round.date < *previous) {
+                self.report(
+                    path,
+                    Some(document.line_of("date", i)),
+                    format!(
+                        "{}: round on {} is out of order, after {}",
+                        round.name, round.date, previous
+                    ),
+                );
+            }
+            previous = Some(previous.map_or(round.date, |previous| previous.max(round.date)));
+        }
+    }
+
+    /// Intervals, from their first through their last day, must not overlap
+    fn check_overlaps(
+        &mut self,
+        path: &Path,
+        kind: &str,
+        intervals: impl Iterator<Item = (usize, NaiveDate, NaiveDate)>,
+    ) {
+        let mut intervals: Vec<(usize, NaiveDate, NaiveDate)> = intervals.collect();
+        intervals.sort_by_key(|(_, starts_on, _)| *starts_on);
+
+        let mut previous: Option<(NaiveDate, NaiveDate)> = None;
+        for (line, starts_on, ends_on) in intervals {
+            if let Some((previous_starts_on, previous_ends_on)) = previous {
+                if starts_on <= previous_ends_on {
+                    self.report(
+                        path,
+                        Some(line),
+                        format!(
+                            "{} from {} overlaps the one from {} to {}",
+                            kind, starts_on, previous_starts_on, previous_ends_on
+                        ),
+                    );
+                }
+            }
+            if previous.is_none_or(|(_, previous_ends_on)| ends_on > previous_ends_on) {
+                previous = Some((starts_on, ends_on));
+            }
+        }
+    }
+
+    fn check_leaves(&mut self, path: &Path, leaves: &[(Document, Leave)]) {
+        for (document, leave) in leaves {
+            if let Err(message) = leave.check() {
+                self.report(path, Some(document.line_of("ends_on", 0)), message);
+            }
+        }
+        self.check_overlaps(
+            path,
+            "leave",
+            leaves.iter().map(|(document, leave)| {
+                (
+                    document.line_of("starts_on", 0),
+                    leave.starts_on,
+                    leave.ends_on,
+                )
+            }),
+        );
+    }
+
+    fn check_part_time(&mut self, path: &Path, intervals: &[(Document, PartTime)]) {
+        for (document, interval) in intervals {
+            if let Err(message) = interval.check() {
+                let key = if interval.ends_on < interval.starts_on {
+                    "ends_on"
+                } else {
+                    "percentage"
+                };
+                self.report(path, Some(document.line_of(key, 0)), message);
+            }
+        }
+        self.check_overlaps(
+            path,
+            "part-time interval",
+            intervals.iter().map(|(document, interval)| {
+                (
+                    document.line_of("starts_on", 0),
+                    interval.starts_on,
+                    interval.ends_on,
+                )
+            }),
+        );
+    }
+}
+
+impl Validator {
+    /// The prices and grants of a single-file portfolio
+    fn validate_portfolio_file(&mut self, path: &Path) {
+        let Some(contents) = self.read(path) else {
+            return;
+        };
+
+        let portfolio = if is_toml(path) {
+            deserialize_toml(path, &contents, self.strict).map_err(|e| (None, e.to_string()))
+        } else {
+            deserialize_document::<Portfolio>(path, &contents, self.strict).map_err(|e| match e {
+                LoadError::DeserializationFailed(e) => (Some(e.line()), e.summary()),
+                e => (None, e.to_string()),
+            })
+        };
+
+        let portfolio = match portfolio {
+            Ok(portfolio) => portfolio,
+            Err((line, message)) => {
+                self.report(path, line, message);
+                return;
+            }
+        };
+
+        self.check_psp(path, &located(&contents, "prices", portfolio.prices));
+        if let Some(history) = &portfolio.price_history {
+            self.validate_price_history(&history_path(path, history));
+        }
+        self.check_option_grants(path, &located(&contents, "options", portfolio.options));
+        self.check_rsu_grants(path, &located(&contents, "rsus", portfolio.rsus));
+        self.check_scenarios(path, &located(&contents, "scenarios", portfolio.scenarios));
+        if let Some(cap_table) = &portfolio.cap_table {
+            let document = section(&contents, "cap_table").unwrap_or(Document {
+                index: 0,
+                line: 0,
+                text: "",
+            });
+            self.check_cap_table(path, &document, cap_table);
+        }
+        self.check_leaves(path, &located(&contents, "leaves", portfolio.leaves));
+        self.check_part_time(path, &located(&contents, "part_time", portfolio.part_time));
+    }
+}
+
+/// Each item in a section of a single-file portfolio, with where it is
+fn located<'a, T>(contents: &'a str, section: &str, items: Vec<T>) -> Vec<(Document<'a>, T)> {
+    let documents = section_items(contents, section);
+    if documents.len() == items.len() {
+        documents.into_iter().zip(items).collect()
+    } else {
+        // Written in a way the items cannot be found, such as flow style
+        items
+            .into_iter()
+            .map(|item| {
+                let document = Document {
+                    index: 0,
+                    line: 0,
+                    text: "",
+                };
+                (document, item)
+            })
+            .collect()
+    }
+}
+
+/// Every problem found in the portfolio's files, in file order
+pub fn validate_portfolio(portfolio_path: &Path, strict: bool) -> Vec<Diagnostic> {
+    let mut validator = Validator::new(strict);
+
+    if !portfolio_path.exists() {
+        validator.report(portfolio_path, None, "no portfolio found".to_string());
+        return validator.diagnostics;
+    }
+
+    if portfolio_path.is_file() {
+        validator.validate_portfolio_file(portfolio_path);
+        return validator.diagnostics;
+    }
+
+    validator.validate_file(&portfolio_path.join("psp.yaml"));
+    validator.validate_price_history(&portfolio_path.join("psp.csv"));
+    for file in [
+        "option_grants.yaml",
+        "rsu_grants.yaml",
+        "scenarios.yaml",
+        "cap_table.yaml",
+        "leaves.yaml",
+        "part_time.yaml",
+    ] {
+        validator.validate_file(&portfolio_path.join(file));
+    }
+
+    validator.diagnostics
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PSP: &str = "date: 2024-01-01\nprice: 1.00\n---\ndate: 2024-06-01\nprice: 2.00\n";
+
+    const OPTIONS: &str = "\
+name: Options
+date: 2024-01-01
+grant_value:
+  exercise_price: 1.00
+  shares: 100
+vesting_schedule:
+  commences_on: 2024-01-01
+  events:
+    - date: 2025-01-01
+      number_of_shares: 100
+";
+
+    const RSUS: &str = "\
+name: RSUs
+date: 2024-01-01
+grant_value:
+  grant_price: 10.00
+  total_value: 1000.00
+vesting_schedule:
+  commences_on: 2024-01-01
+  events:
+    - date: 2025-01-01
+      number: 100
+";
+
+    const SCENARIOS: &str = "\
+name: Flat
+---
+name: Growth
+valuations:
+  - date: 2025-01-01
+    price: 2.00
+growth:
+  annual_rate: 0.1
+  until: 2030-01-01
+";
+
+    const CAP_TABLE: &str = "\
+common_shares: 1000
+option_pool: 100
+preferred:
+  - name: Seed
+    shares: 500
+    issue_price: 0.50
+rounds:
+  - name: Series A
+    date: 2024-06-01
+    new_shares: 300
+    price: 2.00
+  - name: Series B
+    date: 2025-06-01
+    new_shares: 200
+    price: 5.00
+";
+
+    const LEAVES: &str = "\
+starts_on: 2024-01-01
+ends_on: 2024-01-31
+policy: toll
+---
+starts_on: 2024-03-01
+ends_on: 2024-03-31
+policy: suspend
+";
+
+    const PART_TIME: &str = "\
+starts_on: 2024-01-01
+ends_on: 2024-06-30
+percentage: 60
+policy: defer
+---
+starts_on: 2024-07-01
+ends_on: 2024-12-31
+percentage: 80
+policy: forfeit
+";
+
+    /// What validating `contents` as the portfolio file `file` finds
+    fn check(file: &str, contents: &str) -> Vec<String> {
+        let mut validator = Validator::new(true);
+        validator.check_file(Path::new(file), contents);
+        validator
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_files() {
+        for (file, contents) in [
+            ("psp.yaml", PSP),
+            ("option_grants.yaml", OPTIONS),
+            ("rsu_grants.yaml", RSUS),
+            ("scenarios.yaml", SCENARIOS),
+            ("cap_table.yaml", CAP_TABLE),
+            ("leaves.yaml", LEAVES),
+            ("part_time.yaml", PART_TIME),
+        ] {
+            assert_eq!(Vec::<String>::new(), check(file, contents), "{}", file);
+        }
+    }
+
+    #[test]
+    fn test_deserialization_failed() {
+        assert_eq!(1, check("psp.yaml", &PSP.replace("2.00", "two")).len());
+        assert_eq!(1, check("cap_table.yaml", "option_pool: 100\n").len());
+    }
+
+    #[test]
+    fn test_unknown_key() {
+        let diagnostics = check(
+            "psp.yaml",
+            &PSP.replace("price: 2.00", "prise: 2.00\nprice: 2.00"),
+        );
+        assert_eq!(1, diagnostics.len());
+        assert!(
+            diagnostics[0].starts_with("psp.yaml:5:"),
+            "{:?}",
+            diagnostics
+        );
+
+        let mut validator = Validator::new(false);
+        validator.check_file(
+            Path::new("psp.yaml"),
+            &PSP.replace("price: 2.00", "prise: 2.00\nprice: 2.00"),
+        );
+        assert!(validator.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_price_not_positive() {
+        assert!(check("psp.yaml", &PSP.replace("2.00", "0.01")).is_empty());
+        assert_eq!(
+            vec!["psp.yaml:5: price 0 on 2024-06-01 is not positive"],
+            check("psp.yaml", &PSP.replace("2.00", "0"))
+        );
+    }
+
+    #[test]
+    fn test_valuation_order() {
+        assert_eq!(
+            vec!["psp.yaml:4: duplicate valuation on 2024-01-01"],
+            check("psp.yaml", &PSP.replace("2024-06-01", "2024-01-01"))
+        );
+        assert_eq!(
+            vec!["psp.yaml:4: valuation on 2023-06-01 is out of order, after 2024-01-01"],
+            check("psp.yaml", &PSP.replace("2024-06-01", "2023-06-01"))
+        );
+    }
+
+    #[test]
+    fn test_price_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("psp.csv");
+
+        let mut validator = Validator::new(true);
+        fs::write(&path, "Date,Close/Last\n01/05/2024,$10.25\n").unwrap();
+        validator.validate_price_history(&path);
+        assert!(validator.diagnostics.is_empty());
+
+        fs::write(&path, "Date,Close/Last\n01/05/2024,ten\n").unwrap();
+        validator.validate_price_history(&path);
+        assert_eq!(1, validator.diagnostics.len());
+        assert_eq!(Some(2), validator.diagnostics[0].line);
+    }
+
+    #[test]
+    fn test_duplicate_grant_names() {
+        let options = format!("{}---\n{}", OPTIONS, OPTIONS.replace("Options", "More"));
+        assert!(check("option_grants.yaml", &options).is_empty());
+
+        let options = format!("{}---\n{}", OPTIONS, OPTIONS);
+        assert_eq!(
+            vec!["option_grants.yaml:12: duplicate grant name \"Options\", first used on line 1"],
+            check("option_grants.yaml", &options)
+        );
+
+        let mut validator = Validator::new(true);
+        validator.check_file(Path::new("option_grants.yaml"), OPTIONS);
+        validator.check_file(Path::new("rsu_grants.yaml"), RSUS);
+        assert!(validator.diagnostics.is_empty());
+        validator.check_file(
+            Path::new("rsu_grants.yaml"),
+            &RSUS.replace("RSUs", "Options"),
+        );
+        assert_eq!(
+            "rsu_grants.yaml:1: duplicate grant name \"Options\", first used in option_grants.yaml on line 1",
+            validator.diagnostics[0].to_string()
+        );
+    }
+
+    #[test]
+    fn test_event_dates() {
+        let before_commencement =
+            OPTIONS.replace("commences_on: 2024-01-01", "commences_on: 2025-06-01");
+        assert_eq!(
+            vec!["option_grants.yaml:9: Options: vesting event on 2025-01-01 is before vesting commences on 2025-06-01"],
+            check("option_grants.yaml", &before_commencement)
+        );
+
+        let before_grant = RSUS.replace("date: 2024-01-01", "date: 2025-06-01");
+        assert_eq!(
+            vec!["rsu_grants.yaml:9: RSUs: vesting event on 2025-01-01 is before the grant date 2025-06-01"],
+            check("rsu_grants.yaml", &before_grant)
+        );
+    }
+
+    #[test]
+    fn test_rule() {
+        let events = "  events:\n    - date: 2025-01-01\n      number_of_shares: 100\n";
+        let rule = "  rule:\n    months: 12\n    every_months: 12\n";
+        assert!(check("option_grants.yaml", &OPTIONS.replace(events, rule)).is_empty());
+
+        assert_eq!(
+            vec!["option_grants.yaml:8: Options: vesting rule months must be positive"],
+            check(
+                "option_grants.yaml",
+                &OPTIONS.replace(events, &rule.replace("every_months: 12", "every_months: 0"))
+            )
+        );
+        assert_eq!(
+            vec!["option_grants.yaml:8: Options: vesting rule cliff of 13 months is after it fully vests at 12 months"],
+            check(
+                "option_grants.yaml",
+                &OPTIONS.replace(events, &format!("{}    cliff_months: 13\n", rule))
+            )
+        );
+        assert_eq!(
+            vec!["option_grants.yaml:8: Options: vesting schedule has both a rule and events, the events are ignored"],
+            check("option_grants.yaml", &OPTIONS.replace(events, &format!("{}{}", rule, events)))
+        );
+    }
+
+    #[test]
+    fn test_exercise_price_not_positive() {
+        assert!(check("option_grants.yaml", &OPTIONS.replace("1.00", "0.01")).is_empty());
+        assert_eq!(
+            vec!["option_grants.yaml:4: Options: exercise price 0 is not positive"],
+            check("option_grants.yaml", &OPTIONS.replace("1.00", "0"))
+        );
+    }
+
+    #[test]
+    fn test_option_events_total() {
+        assert_eq!(
+            vec!["option_grants.yaml:5: Options: vesting events total 100 shares but the grant is for 120"],
+            check("option_grants.yaml", &OPTIONS.replace("  shares: 100", "  shares: 120"))
+        );
+    }
+
+    #[test]
+    fn test_rsu_value_not_positive() {
+        assert_eq!(
+            vec!["rsu_grants.yaml:4: RSUs: grant price 0 is not positive"],
+            check("rsu_grants.yaml", &RSUS.replace("10.00", "0"))
+        );
+        assert_eq!(
+            vec!["rsu_grants.yaml:5: RSUs: total value -1 is not positive"],
+            check("rsu_grants.yaml", &RSUS.replace("1000.00", "-1"))
+        );
+    }
+
+    #[test]
+    fn test_rsu_events_total() {
+        assert_eq!(
+            vec!["rsu_grants.yaml:5: RSUs: vesting events total 100 units but total value / grant price is 120"],
+            check("rsu_grants.yaml", &RSUS.replace("1000.00", "1200.00"))
+        );
+    }
+
+    #[test]
+    fn test_duplicate_scenario_names() {
+        assert_eq!(
+            vec!["scenarios.yaml:3: duplicate scenario name \"Flat\", first used on line 1"],
+            check("scenarios.yaml", &SCENARIOS.replace("Growth", "Flat"))
+        );
+    }
+
+    #[test]
+    fn test_scenario_price_not_positive() {
+        assert_eq!(
+            vec!["scenarios.yaml:6: Growth: price 0 on 2025-01-01 is not positive"],
+            check("scenarios.yaml", &SCENARIOS.replace("2.00", "0"))
+        );
+    }
+
+    #[test]
+    fn test_scenario_growth_rate() {
+        assert!(check("scenarios.yaml", &SCENARIOS.replace("0.1", "-0.5")).is_empty());
+        assert_eq!(
+            vec!["scenarios.yaml:8: Growth: annual growth rate -1 leaves no value"],
+            check("scenarios.yaml", &SCENARIOS.replace("0.1", "-1"))
+        );
+    }
+
+    #[test]
+    fn test_cap_table_shares() {
+        assert!(check(
+            "cap_table.yaml",
+            &CAP_TABLE.replace("option_pool: 100", "option_pool: 0")
+        )
+        .is_empty());
+        assert_eq!(
+            vec![
+                "cap_table.yaml:1: common shares 0 is not positive",
+                "cap_table.yaml:2: option pool -1 is negative",
+                "cap_table.yaml:5: Seed: shares 0 is not positive",
+                "cap_table.yaml:10: Series A: new shares 0 is not positive",
+            ],
+            check(
+                "cap_table.yaml",
+                &CAP_TABLE
+                    .replace("common_shares: 1000", "common_shares: 0")
+                    .replace("option_pool: 100", "option_pool: -1")
+                    .replace("shares: 500", "shares: 0")
+                    .replace("new_shares: 300", "new_shares: 0")
+            )
+        );
+    }
+
+    #[test]
+    fn test_cap_table_prices() {
+        assert_eq!(
+            vec![
+                "cap_table.yaml:6: Seed: issue price 0 is not positive",
+                "cap_table.yaml:15: Series B: price 0 is not positive",
+            ],
+            check(
+                "cap_table.yaml",
+                &CAP_TABLE.replace("0.50", "0").replace("5.00", "0")
+            )
+        );
+    }
+
+    #[test]
+    fn test_cap_table_round_order() {
+        assert_eq!(
+            vec!["cap_table.yaml:13: Series B: round on 2024-01-01 is out of order, after 2024-06-01"],
+            check("cap_table.yaml", &CAP_TABLE.replace("2025-06-01", "2024-01-01"))
+        );
+    }
+
+    #[test]
+    fn test_overlaps() {
+        assert_eq!(
+            vec!["leaves.yaml:5: leave from 2024-01-31 overlaps the one from 2024-01-01 to 2024-01-31"],
+            check("leaves.yaml", &LEAVES.replace("2024-03-01", "2024-01-31"))
+        );
+        assert_eq!(
+            vec!["part_time.yaml:6: part-time interval from 2024-06-30 overlaps the one from 2024-01-01 to 2024-06-30"],
+            check("part_time.yaml", &PART_TIME.replace("2024-07-01", "2024-06-30"))
+        );
+    }
+
+    #[test]
+    fn test_inverted_leave() {
+        assert_eq!(
+            vec!["leaves.yaml:6: leave ends on 2024-02-29, before it starts on 2024-03-01"],
+            check("leaves.yaml", &LEAVES.replace("2024-03-31", "2024-02-29"))
+        );
+    }
+
+    #[test]
+    fn test_part_time_interval() {
+        assert!(check("part_time.yaml", &PART_TIME.replace("80", "100")).is_empty());
+        assert_eq!(
+            vec!["part_time.yaml:8: part-time percentage 250 is not more than 0 and at most 100"],
+            check("part_time.yaml", &PART_TIME.replace("80", "250"))
+        );
+        assert_eq!(
+            vec!["part_time.yaml:3: part-time percentage 0 is not more than 0 and at most 100"],
+            check("part_time.yaml", &PART_TIME.replace("60", "0"))
+        );
+        assert_eq!(
+            vec!["part_time.yaml:7: part-time interval ends on 2024-06-30, before it starts on 2024-07-01"],
+            check("part_time.yaml", &PART_TIME.replace("2024-12-31", "2024-06-30"))
+        );
+    }
+
+    #[test]
+    fn test_portfolio_file() {
+        let portfolio = "\
+prices:
+  - date: 2024-01-01
+    price: 1.00
+options:
+  - name: Options
+    date: 2024-01-01
+    grant_value:
+      exercise_price: 1.00
+      shares: 100
+    vesting_schedule:
+      commences_on: 2024-01-01
+      events:
+        - date: 2025-01-01
+          number_of_shares: 100
+cap_table:
+  common_shares: 1000
+  preferred:
+    - name: Seed
+      shares: 500
+      issue_price: 0.50
+leaves:
+  - starts_on: 2024-01-01
+    ends_on: 2024-01-31
+    policy: toll
+";
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("portfolio.yaml");
+
+        fs::write(&path, portfolio).unwrap();
+        assert!(validate_portfolio(&path, true).is_empty());
+
+        fs::write(
+            &path,
+            portfolio.replace("issue_price: 0.50", "issue_price: 0"),
+        )
+        .unwrap();
+        let diagnostics = validate_portfolio(&path, true);
+        assert_eq!(1, diagnostics.len());
+        assert_eq!(Some(20), diagnostics[0].line);
+        assert_eq!(
+            "Seed: issue price 0 is not positive",
+            diagnostics[0].message
+        );
+    }
+
+    #[test]
+    fn test_no_portfolio() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(validate_portfolio(dir.path(), true).is_empty());
+        assert_eq!(
+            1,
+            validate_portfolio(&dir.path().join("missing"), true).len()
+        );
+    }
+}
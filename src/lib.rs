@@ -0,0 +1,43 @@
+//! Value equity grants, stock options and RSUs, as they vest over time.
+//!
+//! Load a portfolio with [`portfolio::Portfolio::load`], then build reports
+//! from its prices and grants. Reports hold their results in public fields,
+//! and write them only when asked to, to any [`std::io::Write`]:
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! use equitycalc::{portfolio::Portfolio, report::summary::Summary};
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let portfolio = Portfolio::load(Path::new("portfolio.yaml"), false)?;
+//! let today = chrono::Local::now().date_naive();
+//! let summary = Summary::new(
+//!     &portfolio.psp,
+//!     &portfolio.option_grants,
+//!     &portfolio.rsu_grants,
+//!     today,
+//! );
+//!
+//! for grant in &summary.grants {
+//!     println!("{}: {} vested", grant.name, grant.vested);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+#![warn(missing_docs)]
+// The models sort vesting events and prices with date comparators
+#![allow(clippy::unnecessary_sort_by)]
+
+/// Reading, checking and writing portfolio files
+pub mod dto;
+
+/// Grants, prices and the rules that change how grants vest
+pub mod model;
+
+/// Loading a portfolio and modelling what happens to it
+pub mod portfolio;
+
+/// Reports built from a portfolio's prices and grants
+pub mod report;
@@ -1,13 +1,15 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
 use std::path::Path;
 use std::path::PathBuf;
 
+use anyhow::Context;
+use chrono::{Datelike, NaiveDate};
 use clap::Parser;
 
-mod dto;
-mod model;
-mod report;
-
 use clap::Subcommand;
+use clap::ValueEnum;
+use equitycalc::{dto, model, portfolio::Portfolio, report};
 
 #[derive(Parser)]
 #[command()]
@@ -15,107 +17,815 @@ struct Cli {
     #[clap(subcommand)]
     command: Command,
 
-    /// Location of portfolio files
-    #[arg(long = "portfolio-path", default_value = ".")]
-    portfolio_path: String,
+    /// Location of portfolio files: a directory of files, or a single portfolio.yaml or portfolio.toml [default: .]
+    #[arg(long = "portfolio-path")]
+    portfolio_path: Option<String>,
+
+    /// Model leaving the company on this date, as YYYY-MM-DD, for reports and compare. Unvested shares are forfeited and vested options expire after each grant's post-termination exercise period
+    #[arg(long = "terminate-on", global = true)]
+    terminate_on: Option<NaiveDate>,
+
+    /// Reject keys in portfolio files that do not match any field, instead of ignoring them
+    #[arg(long = "strict", global = true)]
+    strict: bool,
 }
 
 #[derive(Subcommand)]
 enum Command {
+    #[command(flatten)]
+    Report(ReportCommand),
+
+    /// Compare the value vesting each year across several portfolios
+    Compare(CompareArgs),
+
+    /// Check the portfolio files, reporting every problem found
+    Validate,
+
+    /// Print the JSON Schema for documents in a portfolio file
+    Schema(SchemaArgs),
+
+    /// Import grants from an equity platform's CSV export into the grant files
+    Import(ImportArgs),
+
+    /// Rewrite the portfolio files in canonical form
+    #[command(visible_alias = "export")]
+    Fmt(FmtArgs),
+}
+
+// Commands that report on the loaded portfolio
+#[derive(Subcommand)]
+enum ReportCommand {
     /// Generate report of vested, unvested, and total equity value per day
     TotalReport(TotalReportArgs),
 
     /// Generate report of the equity vesting per quarter
     IncrementalReport(IncrementalReportArgs),
+
+    /// Print vested and unvested shares and value per grant as of a date
+    Summary(SummaryArgs),
+
+    /// List upcoming vesting events, optionally exporting them to an iCalendar file
+    Calendar(CalendarArgs),
+
+    /// Simulate future prices and report percentile bands of equity value
+    Simulate(SimulateArgs),
+
+    /// Value grants at the common share price resulting from an exit
+    Exit(ExitArgs),
+
+    /// Show ownership of the fully diluted shares after each planned funding round
+    Dilution(DilutionArgs),
+
+    /// Warn about vested options that must be exercised soon
+    Expiring(ExpiringArgs),
+}
+
+#[derive(Parser)]
+struct FmtArgs {
+    /// Write the portfolio here instead of in place: a directory, or a single .yaml or .toml file
+    #[arg(long = "output")]
+    pub output: Option<String>,
+}
+
+#[derive(Parser)]
+struct ImportArgs {
+    /// CSV export listing each grant's vesting events, one per row
+    pub csv: String,
+
+    /// Layout of the export [default: detected from its header row]
+    #[arg(long = "format", value_enum)]
+    pub format: Option<ImportFormatArg>,
+
+    /// Print the changes to the portfolio's grants without writing them
+    #[arg(long = "dry-run")]
+    pub dry_run: bool,
+}
+
+#[derive(Clone, ValueEnum)]
+enum ImportFormatArg {
+    Carta,
+    Shareworks,
+    Etrade,
+}
+
+impl ImportFormatArg {
+    fn to_import_format(&self) -> dto::import::ImportFormat {
+        match self {
+            ImportFormatArg::Carta => dto::import::ImportFormat::Carta,
+            ImportFormatArg::Shareworks => dto::import::ImportFormat::Shareworks,
+            ImportFormatArg::Etrade => dto::import::ImportFormat::ETrade,
+        }
+    }
+}
+
+#[derive(Parser)]
+struct SchemaArgs {
+    /// Portfolio file to describe
+    #[arg(value_enum)]
+    pub file: SchemaFileArg,
+}
+
+#[derive(Clone, ValueEnum)]
+enum SchemaFileArg {
+    /// psp.yaml
+    Psp,
+
+    /// option_grants.yaml
+    OptionGrants,
+
+    /// rsu_grants.yaml
+    RsuGrants,
+}
+
+impl SchemaFileArg {
+    fn to_schema_file(&self) -> dto::SchemaFile {
+        match self {
+            SchemaFileArg::Psp => dto::SchemaFile::Psp,
+            SchemaFileArg::OptionGrants => dto::SchemaFile::OptionGrants,
+            SchemaFileArg::RsuGrants => dto::SchemaFile::RsuGrants,
+        }
+    }
 }
 
 #[derive(Parser)]
 struct TotalReportArgs {
+    /// First day of the report, as YYYY-MM-DD [default: when vesting commences]
+    #[arg(long = "from")]
+    pub from: Option<NaiveDate>,
+
+    /// Last day of the report, as YYYY-MM-DD. May be after the last vesting event [default: last vesting event]
+    #[arg(long = "to")]
+    pub to: Option<NaiveDate>,
+
+    /// Report one row per period, valued at the end of the period
+    #[arg(long = "granularity", value_enum, default_value_t = GranularityArg::Day)]
+    pub granularity: GranularityArg,
+
+    /// Write one report per scenario in scenarios.yaml, named after the scenario
+    #[arg(long = "scenarios", default_value = "false")]
+    pub scenarios: bool,
+
     /// Destination file name
     #[arg(long = "destination", default_value = "total.csv")]
     pub destination: String,
 }
 
+#[derive(Parser)]
+struct SimulateArgs {
+    /// Number of price paths to simulate
+    #[arg(long = "paths", default_value_t = 1000)]
+    pub paths: u32,
+
+    /// Expected annual return of the price, e.g. 0.1 for 10%
+    #[arg(long = "drift", default_value_t = 0.1)]
+    pub drift: f64,
+
+    /// Annualized volatility of the price, e.g. 0.5 for 50%
+    #[arg(long = "volatility", default_value_t = 0.5)]
+    pub volatility: f64,
+
+    /// Seed for the random number generator
+    #[arg(long = "seed", default_value_t = 0)]
+    pub seed: u64,
+
+    /// Last day to simulate, as YYYY-MM-DD [default: last vesting event]
+    #[arg(long = "until")]
+    pub until: Option<NaiveDate>,
+
+    /// Report one row per period, valued at the end of the period
+    #[arg(long = "granularity", value_enum, default_value_t = GranularityArg::Month)]
+    pub granularity: GranularityArg,
+
+    /// Destination file name
+    #[arg(long = "destination", default_value = "simulation.csv")]
+    pub destination: String,
+}
+
+impl SimulateArgs {
+    pub fn to_simulation_options(&self) -> report::simulate::SimulationOptions {
+        report::simulate::SimulationOptions {
+            paths: self.paths,
+            drift: self.drift,
+            volatility: self.volatility,
+            seed: self.seed,
+            until: self.until,
+            granularity: self.granularity.to_granularity(),
+        }
+    }
+}
+
+#[derive(Parser)]
+struct ExitArgs {
+    /// Exit valuation paid to all shareholders, in dollars
+    #[arg(long = "valuation", value_parser = parse_valuation)]
+    pub valuation: f64,
+
+    /// Date of the exit, as YYYY-MM-DD [default: today]
+    #[arg(long = "on")]
+    pub on: Option<NaiveDate>,
+}
+
+#[derive(Parser)]
+struct DilutionArgs {
+    /// Date to report current ownership on, as YYYY-MM-DD [default: today]
+    #[arg(long = "as-of")]
+    pub as_of: Option<NaiveDate>,
+}
+
+#[derive(Parser)]
+struct CompareArgs {
+    /// Location of a portfolio to compare (repeatable)
+    #[arg(long = "portfolio", required = true)]
+    pub portfolios: Vec<String>,
+
+    /// Number of years, from when each portfolio's vesting commences, to compare
+    #[arg(long = "horizon-years", default_value_t = 4)]
+    pub horizon_years: u32,
+
+    /// Destination file name
+    #[arg(long = "destination", default_value = "compare.csv")]
+    pub destination: String,
+}
+
+#[derive(Parser)]
+struct ExpiringArgs {
+    /// Warn about options that can last be exercised within this many months
+    #[arg(long = "within-months", default_value_t = 12)]
+    pub within_months: u32,
+
+    /// Date to look ahead from, as YYYY-MM-DD [default: today]
+    #[arg(long = "as-of")]
+    pub as_of: Option<NaiveDate>,
+}
+
+#[derive(Clone, ValueEnum)]
+enum GranularityArg {
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl TotalReportArgs {
+    pub fn to_valuation_options(&self) -> report::total::ValuationOptions {
+        report::total::ValuationOptions {
+            from: self.from,
+            to: self.to,
+            granularity: self.granularity.to_granularity(),
+        }
+    }
+}
+
+impl GranularityArg {
+    pub fn to_granularity(&self) -> report::total::Granularity {
+        match self {
+            GranularityArg::Day => report::total::Granularity::Day,
+            GranularityArg::Week => report::total::Granularity::Week,
+            GranularityArg::Month => report::total::Granularity::Month,
+            GranularityArg::Quarter => report::total::Granularity::Quarter,
+            GranularityArg::Year => report::total::Granularity::Year,
+        }
+    }
+}
+
 #[derive(Debug, Parser, Default)]
 struct IncrementalReportArgs {
     /// Use quarters of 12/17-3/16, 3/17-6/16, 6/17-9/16, and 9/17-12/16
-    #[arg(long = "skewed", default_value = "false")]
+    #[arg(long = "skewed", default_value = "false", conflicts_with_all = ["period", "period_start", "periods_file"])]
     pub skewed_quarter_dates: bool,
 
+    /// Length of each report period
+    #[arg(long = "period", value_enum, default_value_t = PeriodArg::Quarter)]
+    pub period: PeriodArg,
+
+    /// Month and day, as MM-DD, that one period of each year starts on (e.g. a fiscal year start). In months without that day, periods start on the last day of the month
+    #[arg(long = "period-start", default_value = "01-01", value_parser = parse_period_start)]
+    pub period_start: (u32, u32),
+
+    /// YAML file listing period boundary dates, for custom periods. Shares vesting before the first or on or after the last boundary are left out of the report
+    #[arg(long = "periods-file", conflicts_with_all = ["period", "period_start"])]
+    pub periods_file: Option<String>,
+
+    /// Add columns with the number of shares vesting per grant and in total
+    #[arg(long = "shares", default_value = "false")]
+    pub shares: bool,
+
+    /// Add columns with the cumulative shares and value vested
+    #[arg(long = "cumulative", default_value = "false")]
+    pub cumulative: bool,
+
+    /// Add a column with the preferred stock price at the end of each period, for reference. Shares are valued at the price on their vest date or the grant's vest_price, not this price
+    #[arg(long = "price", default_value = "false")]
+    pub price: bool,
+
+    /// Write one report per scenario in scenarios.yaml, named after the scenario
+    #[arg(long = "scenarios", default_value = "false")]
+    pub scenarios: bool,
+
     /// Destination file name
     #[arg(long = "destination", default_value = "incremental.csv")]
     pub destination: String,
 }
 
+#[derive(Parser)]
+struct SummaryArgs {
+    /// Date to summarize the portfolio on, as YYYY-MM-DD [default: today]
+    #[arg(long = "as-of")]
+    pub as_of: Option<NaiveDate>,
+}
+
+#[derive(Parser)]
+struct CalendarArgs {
+    /// Only list events on or after this date, as YYYY-MM-DD [default: today]
+    #[arg(long = "from")]
+    pub from: Option<NaiveDate>,
+
+    /// Also write the events to this iCalendar (.ics) file
+    #[arg(long = "ics")]
+    pub ics: Option<String>,
+
+    /// Add a reminder this many days before each vest (repeatable)
+    #[arg(long = "remind-days-before")]
+    pub remind_days_before: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Default, ValueEnum)]
+enum PeriodArg {
+    Month,
+    #[default]
+    Quarter,
+    HalfYear,
+    Year,
+}
+
+fn parse_valuation(value: &str) -> Result<f64, String> {
+    let valuation: f64 = value
+        .parse()
+        .map_err(|_| format!("expected a number, got {}", value))?;
+    if !valuation.is_finite() || valuation <= 0.0 {
+        return Err("valuation must be more than 0".to_string());
+    }
+    Ok(valuation)
+}
+
+fn parse_period_start(value: &str) -> Result<(u32, u32), String> {
+    // 2000 is a leap year, so every month and day is accepted
+    let date = NaiveDate::parse_from_str(&format!("2000-{}", value), "%Y-%m-%d")
+        .map_err(|_| format!("expected MM-DD, got {}", value))?;
+    Ok((date.month(), date.day()))
+}
+
 impl IncrementalReportArgs {
-    pub fn to_report_options(&self) -> report::incr::ReportOptions {
-        report::incr::ReportOptions {
-            quarter_type: if self.skewed_quarter_dates {
-                report::incr::QuarterType::Skewed
-            } else {
-                report::incr::QuarterType::Calendar
+    pub fn to_report_options(&self, strict: bool) -> dto::Result<report::incr::ReportOptions> {
+        let period_type = if self.skewed_quarter_dates {
+            report::incr::PeriodType::skewed_quarter()
+        } else if let Some(periods_file) = &self.periods_file {
+            report::incr::PeriodType::Custom(dto::load_period_boundaries(
+                Path::new(periods_file),
+                strict,
+            )?)
+        } else {
+            let (start_month, start_day) = self.period_start;
+            let months = match self.period {
+                PeriodArg::Month => 1,
+                PeriodArg::Quarter => 3,
+                PeriodArg::HalfYear => 6,
+                PeriodArg::Year => 12,
+            };
+            report::incr::PeriodType::recurring(months, start_month, start_day)
+                .map_err(anyhow::Error::msg)?
+        };
+
+        Ok(report::incr::ReportOptions {
+            period_type,
+            columns: report::incr::ColumnGroups {
+                shares: self.shares,
+                cumulative: self.cumulative,
+                price: self.price,
             },
-        }
+        })
     }
 }
 
-fn run_command(command: Command, portfolio: PortfolioContext) {
+fn run_report(command: ReportCommand, portfolio: Portfolio) -> Result<(), anyhow::Error> {
     match command {
-        Command::TotalReport(args) => {
-            let destination = PathBuf::from(&args.destination);
+        ReportCommand::TotalReport(args) => {
+            for run in report_runs(&portfolio, args.scenarios, &args.destination)? {
+                let valuation = report::total::Valuation::new(
+                    &run.psp,
+                    &run.option_grants,
+                    &run.rsu_grants,
+                    args.to_valuation_options(),
+                );
 
-            let valuation = report::total::Valuation::new(
+                write_report(&run.destination, |out| valuation.print(out))?;
+
+                println!("Wrote total report to {:?}", run.destination)
+            }
+        }
+        ReportCommand::IncrementalReport(args) => {
+            for run in report_runs(&portfolio, args.scenarios, &args.destination)? {
+                let report = report::incr::Report::new(
+                    &run.psp,
+                    &run.option_grants,
+                    &run.rsu_grants,
+                    args.to_report_options(portfolio.files.strict())?,
+                )
+                .map_err(anyhow::Error::msg)?;
+
+                write_report(&run.destination, |out| report.print(out))?;
+
+                println!("Wrote incremental report to {:?}", run.destination);
+                if report.unreported_shares > 0 {
+                    println!(
+                        "{} shares vest outside the custom periods and are not in the report",
+                        report.unreported_shares
+                    );
+                }
+            }
+        }
+        ReportCommand::Summary(args) => {
+            let as_of = args
+                .as_of
+                .unwrap_or_else(|| chrono::Local::now().date_naive());
+
+            let summary = report::summary::Summary::new(
                 &portfolio.psp,
                 &portfolio.option_grants,
                 &portfolio.rsu_grants,
+                as_of,
             );
 
-            valuation.print_to_file(&destination);
+            summary.print(&mut std::io::stdout())?;
+        }
+        ReportCommand::Calendar(args) => {
+            let from = args
+                .from
+                .unwrap_or_else(|| chrono::Local::now().date_naive());
+
+            let calendar = report::calendar::Calendar::new(
+                &portfolio.psp,
+                &portfolio.option_grants,
+                &portfolio.rsu_grants,
+                from,
+            );
 
-            println!("Wrote total report to {:?}", destination)
+            calendar.print(&mut std::io::stdout())?;
+
+            if let Some(ics) = args.ics {
+                let destination = PathBuf::from(&ics);
+                write_report(&destination, |out| {
+                    calendar.print_ics(out, &args.remind_days_before)
+                })?;
+                println!("Wrote vesting calendar to {:?}", destination);
+            }
         }
-        Command::IncrementalReport(args) => {
+        ReportCommand::Simulate(args) => {
             let destination = PathBuf::from(&args.destination);
 
-            let report = report::incr::Report::new(
-                &portfolio.psp,
+            let simulation = report::simulate::Simulation::new(
+                &portfolio.known_psp,
                 &portfolio.option_grants,
                 &portfolio.rsu_grants,
-                args.to_report_options(),
+                args.to_simulation_options(),
             );
 
-            report.print_to_file(&destination);
+            write_report(&destination, |out| simulation.print(out))?;
 
-            println!("Wrote incremental report to {:?}", destination);
+            println!("Wrote simulation report to {:?}", destination);
+        }
+        ReportCommand::Exit(args) => {
+            let exit_on = args.on.unwrap_or_else(|| chrono::Local::now().date_naive());
+
+            let cap_table = portfolio
+                .cap_table
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("cap_table.yaml is required to model an exit"))?;
+            let waterfall = cap_table
+                .as_of(&exit_on)
+                .waterfall((args.valuation * 100.0).round() as i64);
+
+            let report = report::exit::ExitReport::new(
+                waterfall,
+                &portfolio.option_grants,
+                &portfolio.rsu_grants,
+                exit_on,
+            );
+
+            report.print(&mut std::io::stdout())?;
+        }
+        ReportCommand::Expiring(args) => {
+            let as_of = args
+                .as_of
+                .unwrap_or_else(|| chrono::Local::now().date_naive());
+
+            let report = report::expiry::ExpiryReport::new(
+                &portfolio.psp,
+                &portfolio.option_grants,
+                as_of,
+                args.within_months,
+            );
+
+            report.print(&mut std::io::stdout())?;
+        }
+        ReportCommand::Dilution(args) => {
+            let as_of = args
+                .as_of
+                .unwrap_or_else(|| chrono::Local::now().date_naive());
+
+            let cap_table = portfolio
+                .cap_table
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("cap_table.yaml is required to model dilution"))?;
+
+            let report = report::dilution::DilutionReport::new(
+                cap_table,
+                &portfolio.psp,
+                &portfolio.option_grants,
+                &portfolio.rsu_grants,
+                as_of,
+            );
+
+            report.print(&mut std::io::stdout())?;
         }
     }
+
+    Ok(())
 }
 
-struct PortfolioContext {
+/// A report to run and where to write it
+struct ReportRun {
+    destination: PathBuf,
     psp: model::psp::PreferredStockPrice,
     option_grants: Vec<model::option::OptionGrant>,
     rsu_grants: Vec<model::rsu::RestrictedStockUnitGrant>,
 }
 
-fn load_portfolio(path: &String) -> dto::Result<PortfolioContext> {
-    let path = Path::new(path);
-    let psp = dto::load_psp(path)?;
-    let option_grants = dto::load_option_grants(path)?;
-    let rsu_grants = dto::load_rsu_grants(path)?;
+/// The reports to run: the portfolio as is, or one per scenario when
+/// `scenarios` is set, with the scenario name added to the destination file
+/// name
+fn report_runs(
+    portfolio: &Portfolio,
+    scenarios: bool,
+    destination: &str,
+) -> dto::Result<Vec<ReportRun>> {
+    let destination = PathBuf::from(destination);
+
+    if !scenarios {
+        return Ok(vec![ReportRun {
+            destination,
+            psp: portfolio.psp.clone(),
+            option_grants: portfolio.option_grants.clone(),
+            rsu_grants: portfolio.rsu_grants.clone(),
+        }]);
+    }
+
+    let stem = destination
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = destination
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+
+    let mut runs: Vec<ReportRun> = Vec::new();
+    let mut names: Vec<String> = Vec::new();
+    for scenario in portfolio.scenarios()? {
+        let file_name = format!("{}-{}{}", stem, report::slugify(&scenario.name), extension);
+        let destination = destination.with_file_name(file_name);
+
+        // Names differing only in punctuation or case share a file name
+        if let Some(i) = runs.iter().position(|run| run.destination == destination) {
+            anyhow::bail!(
+                "scenarios {:?} and {:?} would both be written to {:?}, rename one of them",
+                names[i],
+                scenario.name,
+                destination
+            );
+        }
 
-    Ok(PortfolioContext {
-        psp,
-        option_grants,
-        rsu_grants,
-    })
+        names.push(scenario.name);
+        runs.push(ReportRun {
+            destination,
+            psp: scenario.psp,
+            option_grants: scenario.option_grants,
+            rsu_grants: scenario.rsu_grants,
+        });
+    }
+
+    Ok(runs)
+}
+
+/// Write a report to `destination` with `print`
+fn write_report(
+    destination: &Path,
+    print: impl FnOnce(&mut BufWriter<File>) -> std::io::Result<()>,
+) -> Result<(), anyhow::Error> {
+    let mut file = BufWriter::new(
+        File::create(destination).with_context(|| format!("unable to create {:?}", destination))?,
+    );
+    print(&mut file)
+        .and_then(|_| file.flush())
+        .with_context(|| format!("unable to write {:?}", destination))
+}
+
+/// Load the portfolio at `path`, printing how its vesting was adjusted
+fn load_portfolio(path: &str, strict: bool) -> Result<Portfolio, anyhow::Error> {
+    let portfolio = Portfolio::load(Path::new(path), strict)?;
+
+    for adjustment in &portfolio.adjustments {
+        adjustment.print(&mut std::io::stdout())?;
+    }
+
+    Ok(portfolio)
+}
+
+fn run_validate(path: &str, strict: bool) -> Result<(), anyhow::Error> {
+    let diagnostics = dto::validate::validate_portfolio(Path::new(path), strict);
+
+    for diagnostic in &diagnostics {
+        println!("{}", diagnostic);
+    }
+
+    if diagnostics.is_empty() {
+        println!("No problems found");
+        Ok(())
+    } else {
+        anyhow::bail!("{} problem(s) found", diagnostics.len())
+    }
+}
+
+fn run_import(args: ImportArgs, path: &str, strict: bool) -> Result<(), anyhow::Error> {
+    let portfolio_path = Path::new(path);
+    if portfolio_path.is_file() {
+        anyhow::bail!(
+            "cannot import into the single-file portfolio {}, import into a directory instead",
+            path
+        );
+    }
+
+    let files = dto::PortfolioFiles::open(portfolio_path, strict)?;
+    let psp = dto::load_psp(&files)?;
+    let current_option_grants = dto::load_option_grants(&files)?;
+    let current_rsu_grants = dto::load_rsu_grants(&files)?;
+
+    let import = dto::import::import_grants(
+        Path::new(&args.csv),
+        args.format.as_ref().map(|format| format.to_import_format()),
+        portfolio_path,
+        &psp,
+    )?;
+
+    report::import::ImportReport::new(
+        &current_option_grants,
+        &import.option_grants()?,
+        &current_rsu_grants,
+        &import.rsu_grants()?,
+    )
+    .print(&mut std::io::stdout())?;
+
+    if args.dry_run {
+        println!("Dry run, no files written");
+    } else {
+        for written in import.write(portfolio_path)? {
+            println!("Wrote imported grants to {:?}", written);
+        }
+    }
+
+    Ok(())
+}
+
+/// Short names for the portfolios at `paths`: the file or directory name, or
+/// the whole path when portfolios share a name
+fn portfolio_names(paths: &[String]) -> Vec<String> {
+    let stems: Vec<String> = paths
+        .iter()
+        .map(|path| {
+            Path::new(path)
+                .file_stem()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.clone())
+        })
+        .collect();
+
+    stems
+        .iter()
+        .zip(paths)
+        .map(|(stem, path)| {
+            if stems.iter().filter(|other| *other == stem).count() > 1 {
+                path.clone()
+            } else {
+                stem.clone()
+            }
+        })
+        .collect()
+}
+
+fn run_compare(
+    args: CompareArgs,
+    terminate_on: Option<NaiveDate>,
+    strict: bool,
+) -> Result<(), anyhow::Error> {
+    let destination = PathBuf::from(&args.destination);
+
+    let mut portfolios = Vec::new();
+    for (name, path) in portfolio_names(&args.portfolios)
+        .into_iter()
+        .zip(&args.portfolios)
+    {
+        let mut portfolio = load_portfolio(path, strict)?;
+        if let Some(terminate_on) = terminate_on {
+            portfolio = portfolio.terminate(terminate_on);
+        }
+        portfolios.push((name, portfolio));
+    }
+
+    let compared: Vec<report::compare::ComparedPortfolio> = portfolios
+        .iter()
+        .map(|(name, portfolio)| report::compare::ComparedPortfolio {
+            name: name.clone(),
+            psp: &portfolio.psp,
+            option_grants: &portfolio.option_grants,
+            rsu_grants: &portfolio.rsu_grants,
+        })
+        .collect();
+
+    let comparison = report::compare::Comparison::new(&compared, args.horizon_years)
+        .map_err(anyhow::Error::msg)?;
+
+    write_report(&destination, |out| comparison.print(out))?;
+
+    for (name, total) in comparison.horizon_totals() {
+        println!(
+            "{}: {} vests over {} years",
+            name,
+            report::format_currency(total),
+            args.horizon_years
+        );
+    }
+
+    println!("Wrote comparison report to {:?}", destination);
+
+    Ok(())
 }
 
 fn main() -> Result<(), anyhow::Error> {
     let args = Cli::parse();
+    let portfolio_path = args.portfolio_path.as_deref().unwrap_or(".");
 
-    let portfolio = load_portfolio(&args.portfolio_path)?;
+    // Leaving the company only changes what reports value
+    let needs_terminate_on = matches!(args.command, Command::Report(_) | Command::Compare(_));
+    if args.terminate_on.is_some() && !needs_terminate_on {
+        anyhow::bail!("--terminate-on only applies to reports and compare");
+    }
 
-    run_command(args.command, portfolio);
+    match args.command {
+        Command::Report(command) => {
+            let mut portfolio = load_portfolio(portfolio_path, args.strict)?;
 
-    Ok(())
+            if let Some(terminate_on) = args.terminate_on {
+                report::termination::TerminationReport::new(
+                    &portfolio.psp,
+                    &portfolio.option_grants,
+                    &portfolio.rsu_grants,
+                    terminate_on,
+                )
+                .print(&mut std::io::stdout())?;
+
+                portfolio = portfolio.terminate(terminate_on);
+            }
+
+            run_report(command, portfolio)
+        }
+        Command::Compare(compare_args) => {
+            if args.portfolio_path.is_some() {
+                anyhow::bail!(
+                    "compare takes each portfolio with --portfolio, not --portfolio-path"
+                );
+            }
+            run_compare(compare_args, args.terminate_on, args.strict)
+        }
+        Command::Validate => run_validate(portfolio_path, args.strict),
+        Command::Schema(schema_args) => {
+            let schema = dto::json_schema(&schema_args.file.to_schema_file());
+            let mut out = std::io::stdout();
+            serde_json::to_writer_pretty(&mut out, &schema)?;
+            writeln!(out)?;
+            Ok(())
+        }
+        Command::Import(import_args) => run_import(import_args, portfolio_path, args.strict),
+        Command::Fmt(fmt_args) => {
+            let written = dto::export::export_portfolio(
+                Path::new(portfolio_path),
+                fmt_args.output.as_ref().map(Path::new),
+                args.strict,
+            )?;
+            for path in written {
+                println!("Wrote {:?}", path);
+            }
+            Ok(())
+        }
+    }
 }
@@ -0,0 +1,171 @@
+use chrono::{Months, NaiveDate};
+
+use crate::model::vesting::{normalize, VestingEvent};
+
+/// What accelerates vesting
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Trigger {
+    /// Accelerates on the change of control
+    Single,
+
+    /// Accelerates when the holder leaves on or after the change of control
+    Double,
+}
+
+/// How much vesting accelerates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AccelerationAmount {
+    /// Percentage of the unvested shares, taken evenly from later events
+    Percent(f64),
+
+    /// Shares that would have vested in the following months
+    Months(u32),
+}
+
+/// Vesting acceleration terms on a change of control
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Acceleration {
+    /// What accelerates vesting
+    pub trigger: Trigger,
+
+    /// How much vesting accelerates
+    pub amount: AccelerationAmount,
+}
+
+impl Acceleration {
+    /// Terms accelerating vesting by `amount` when `trigger` happens
+    pub fn new(trigger: Trigger, amount: AccelerationAmount) -> Acceleration {
+        Acceleration { trigger, amount }
+    }
+
+    /// The day accelerated shares vest, if the acquisition and termination
+    /// trigger acceleration
+    pub fn triggered_on(
+        &self,
+        acquired_on: NaiveDate,
+        terminated_on: Option<NaiveDate>,
+    ) -> Option<NaiveDate> {
+        match self.trigger {
+            Trigger::Single => Some(acquired_on),
+            Trigger::Double => terminated_on.filter(|date| date >= &acquired_on),
+        }
+    }
+
+    /// `events` with the accelerated shares vesting on `on`
+    pub fn accelerate<E: VestingEvent>(&self, events: &[E], on: NaiveDate) -> Vec<E> {
+        let (mut result, later): (Vec<E>, Vec<E>) =
+            events.iter().cloned().partition(|event| event.date() <= on);
+
+        match self.amount {
+            AccelerationAmount::Months(months) => {
+                // Months past the last date that can be represented accelerate
+                // every later event
+                let until = on.checked_add_months(Months::new(months));
+                for event in later {
+                    if until.is_none_or(|until| event.date() <= until) {
+                        result.push(event.with(on, event.number()));
+                    } else {
+                        result.push(event);
+                    }
+                }
+            }
+            AccelerationAmount::Percent(percent) => {
+                let unvested: i32 = later.iter().map(|event| event.number()).sum();
+                let accelerated = (unvested as f64 * percent.clamp(0.0, 100.0) / 100.0) as i32;
+                let remaining = unvested - accelerated;
+
+                if let Some(first) = later.first() {
+                    result.push(first.with(on, accelerated));
+                }
+
+                // Scale the cumulative shares vested by each later event, so
+                // the later events add up to exactly the remaining shares
+                let mut cumulative = 0;
+                let mut scaled_cumulative = 0;
+                for event in later.into_iter().filter(|_| unvested > 0) {
+                    cumulative += event.number();
+                    let next = (cumulative as i64 * remaining as i64 / unvested as i64) as i32;
+                    result.push(event.with(event.date(), next - scaled_cumulative));
+                    scaled_cumulative = next;
+                }
+            }
+        }
+
+        normalize(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::rsu::RestrictedStockUnitVestingEvent;
+
+    fn events() -> Vec<RestrictedStockUnitVestingEvent> {
+        (1..=4)
+            .map(|quarter| {
+                RestrictedStockUnitVestingEvent::new(
+                    NaiveDate::from_ymd_opt(2024, quarter * 3, 1).unwrap(),
+                    100,
+                )
+            })
+            .collect()
+    }
+
+    fn summarize(events: &[RestrictedStockUnitVestingEvent]) -> Vec<(u32, i32)> {
+        use chrono::Datelike;
+        events
+            .iter()
+            .map(|event| (event.date.month(), event.number))
+            .collect()
+    }
+
+    #[test]
+    fn test_accelerate_months() {
+        let acceleration = Acceleration::new(Trigger::Single, AccelerationAmount::Months(3));
+        let on = NaiveDate::from_ymd_opt(2024, 4, 15).unwrap();
+
+        assert_eq!(
+            vec![(3, 100), (4, 100), (9, 100), (12, 100)],
+            summarize(&acceleration.accelerate(&events(), on))
+        );
+
+        let acceleration = Acceleration::new(Trigger::Single, AccelerationAmount::Months(u32::MAX));
+        assert_eq!(
+            vec![(3, 100), (4, 300)],
+            summarize(&acceleration.accelerate(&events(), on))
+        );
+    }
+
+    #[test]
+    fn test_accelerate_percent() {
+        let acceleration = Acceleration::new(Trigger::Single, AccelerationAmount::Percent(50.0));
+        let on = NaiveDate::from_ymd_opt(2024, 4, 15).unwrap();
+
+        assert_eq!(
+            vec![(3, 100), (4, 150), (6, 50), (9, 50), (12, 50)],
+            summarize(&acceleration.accelerate(&events(), on))
+        );
+
+        let acceleration = Acceleration::new(Trigger::Single, AccelerationAmount::Percent(100.0));
+        assert_eq!(
+            vec![(3, 100), (4, 300)],
+            summarize(&acceleration.accelerate(&events(), on))
+        );
+    }
+
+    #[test]
+    fn test_triggered_on() {
+        let acquired_on = NaiveDate::from_ymd_opt(2024, 4, 15).unwrap();
+        let left_on = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+
+        let single = Acceleration::new(Trigger::Single, AccelerationAmount::Percent(100.0));
+        assert_eq!(Some(acquired_on), single.triggered_on(acquired_on, None));
+
+        let double = Acceleration::new(Trigger::Double, AccelerationAmount::Percent(100.0));
+        assert_eq!(None, double.triggered_on(acquired_on, None));
+        assert_eq!(
+            Some(left_on),
+            double.triggered_on(acquired_on, Some(left_on))
+        );
+    }
+}
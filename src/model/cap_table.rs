@@ -0,0 +1,344 @@
+use chrono::NaiveDate;
+
+use crate::model::psp::{PreferredStockPrice, PreferredStockPriceValuation};
+
+/// A series of preferred shares and its liquidation preference
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreferredSeries {
+    /// Name of the series, e.g. Series A
+    pub name: String,
+
+    /// Number of preferred shares in the series
+    pub shares: i64,
+
+    /// The price each share was issued at
+    pub issue_price_cents: i64,
+
+    /// Multiple of the issue price paid before junior series and common
+    pub liquidation_multiple: f64,
+
+    /// Whether the series shares in the remaining proceeds after its preference
+    pub participating: bool,
+
+    /// Total return, as a multiple of the issue price, that participation is
+    /// capped at
+    pub participation_cap: Option<f64>,
+
+    /// Series with a higher seniority are paid their preference first. Series
+    /// with the same seniority are paid pari passu.
+    pub seniority: i32,
+}
+
+impl PreferredSeries {
+    fn preference_cents(&self) -> f64 {
+        self.shares as f64 * self.issue_price_cents as f64 * self.liquidation_multiple
+    }
+}
+
+/// A planned future funding round, issuing new preferred shares
+#[derive(Debug, Clone, PartialEq)]
+pub struct FundingRound {
+    /// Name of the round, e.g. Series C
+    pub name: String,
+
+    /// The day the round closes and its price takes effect
+    pub date: NaiveDate,
+
+    /// Number of preferred shares the round issues
+    pub new_shares: i64,
+
+    /// The price per share of the round, in cents
+    pub price_cents: i32,
+}
+
+/// The company's shares, by class, and its planned funding rounds
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapTable {
+    /// Common shares issued
+    pub common_shares: i64,
+
+    /// Options granted and available to grant, treated as common shares
+    pub option_pool: i64,
+
+    /// Preferred series issued so far
+    pub preferred: Vec<PreferredSeries>,
+
+    /// Planned rounds, in date order
+    pub rounds: Vec<FundingRound>,
+}
+
+/// What a preferred series is paid in an exit
+pub struct SeriesPayout {
+    /// Name of the series
+    pub name: String,
+
+    /// Whether the series took common instead of its preference
+    pub converted: bool,
+
+    /// What the whole series is paid
+    pub payout_cents: i64,
+}
+
+/// How the proceeds of an exit are paid out, in cents
+pub struct Waterfall {
+    /// The proceeds of the exit
+    pub exit_value_cents: i64,
+
+    /// What each preferred series is paid, in the cap table's order
+    pub series: Vec<SeriesPayout>,
+
+    /// What all the common shares, including the option pool, are paid
+    pub common_payout_cents: i64,
+
+    /// What each common share is paid
+    pub price_per_common_share_cents: i32,
+}
+
+/// Payout to each series and per common share when `converted` series take
+/// common instead of their preference
+fn distribute(cap_table: &CapTable, exit_value_cents: f64, converted: &[bool]) -> (Vec<f64>, f64) {
+    let series = &cap_table.preferred;
+    let mut payouts = vec![0.0; series.len()];
+    let mut remaining = exit_value_cents;
+
+    // Preferences, most senior first
+    let mut seniorities: Vec<i32> = series.iter().map(|s| s.seniority).collect();
+    seniorities.sort();
+    seniorities.dedup();
+    for seniority in seniorities.iter().rev() {
+        let members: Vec<usize> = (0..series.len())
+            .filter(|i| !converted[*i] && series[*i].seniority == *seniority)
+            .collect();
+        let owed: f64 = members.iter().map(|i| series[*i].preference_cents()).sum();
+        if owed <= 0.0 {
+            continue;
+        }
+
+        let paid = owed.min(remaining);
+        for i in members {
+            payouts[i] = paid * series[i].preference_cents() / owed;
+        }
+        remaining -= paid;
+    }
+
+    // Remaining proceeds are shared by common, converted series and
+    // participating series, until any participation caps are reached
+    let mut sharing: Vec<usize> = (0..series.len())
+        .filter(|i| converted[*i] || series[*i].participating)
+        .collect();
+    let common_shares = (cap_table.common_shares + cap_table.option_pool) as f64;
+
+    loop {
+        let shares: f64 = common_shares
+            + sharing
+                .iter()
+                .map(|i| series[*i].shares as f64)
+                .sum::<f64>();
+        let per_share = if shares > 0.0 {
+            remaining / shares
+        } else {
+            0.0
+        };
+
+        let capped = sharing.iter().position(|i| {
+            let s = &series[*i];
+            !converted[*i]
+                && s.participation_cap.is_some_and(|cap| {
+                    payouts[*i] + per_share * s.shares as f64
+                        > cap * s.issue_price_cents as f64 * s.shares as f64
+                })
+        });
+
+        match capped {
+            Some(position) => {
+                let i = sharing.remove(position);
+                let s = &series[i];
+                let cap_total =
+                    s.participation_cap.unwrap() * s.issue_price_cents as f64 * s.shares as f64;
+                let participation = (cap_total - payouts[i]).max(0.0);
+                payouts[i] += participation;
+                remaining -= participation;
+            }
+            None => {
+                for i in &sharing {
+                    payouts[*i] += per_share * series[*i].shares as f64;
+                }
+                return (payouts, per_share);
+            }
+        }
+    }
+}
+
+impl CapTable {
+    /// A cap table with `rounds` sorted by date
+    pub fn new(
+        common_shares: i64,
+        option_pool: i64,
+        preferred: Vec<PreferredSeries>,
+        rounds: Vec<FundingRound>,
+    ) -> CapTable {
+        let mut rounds = rounds;
+        rounds.sort_by_key(|round| round.date);
+        CapTable {
+            common_shares,
+            option_pool,
+            preferred,
+            rounds,
+        }
+    }
+
+    /// Shares outstanding before any planned rounds, treating the option pool
+    /// and preferred as common
+    pub fn fully_diluted_shares(&self) -> i64 {
+        self.common_shares + self.option_pool + self.preferred.iter().map(|s| s.shares).sum::<i64>()
+    }
+
+    /// `psp` extended with the price of each planned round dated after its
+    /// last valuation
+    pub fn extend_price(&self, psp: &PreferredStockPrice) -> PreferredStockPrice {
+        let last = psp.last_valuation().map(|valuation| valuation.date());
+
+        psp.with_valuations(
+            self.rounds
+                .iter()
+                .filter(|round| last.is_none_or(|last| round.date > last))
+                .map(|round| PreferredStockPriceValuation::new(round.date, round.price_cents))
+                .collect(),
+        )
+    }
+
+    /// The cap table after the planned rounds on or before `date`. Each round
+    /// becomes a 1x non-participating series, senior to all earlier series.
+    pub fn as_of(&self, date: &NaiveDate) -> CapTable {
+        let mut preferred = self.preferred.clone();
+        let most_senior = preferred.iter().map(|s| s.seniority).max().unwrap_or(0);
+
+        let rounds = self.rounds.iter().filter(|round| &round.date <= date);
+        for (round, seniority) in rounds.zip(most_senior + 1..) {
+            preferred.push(PreferredSeries {
+                name: round.name.clone(),
+                shares: round.new_shares,
+                issue_price_cents: round.price_cents as i64,
+                liquidation_multiple: 1.0,
+                participating: false,
+                participation_cap: None,
+                seniority,
+            });
+        }
+
+        CapTable::new(self.common_shares, self.option_pool, preferred, vec![])
+    }
+
+    /// Distribute `exit_value_cents` between the preferred series and common.
+    /// Each series converts to common when that pays it more than its
+    /// preference, given the choices of the other series.
+    pub fn waterfall(&self, exit_value_cents: i64) -> Waterfall {
+        let exit = exit_value_cents as f64;
+        let mut converted = vec![false; self.preferred.len()];
+
+        // Conversion choices almost always settle within a few rounds; the
+        // bound guards against series flipping back and forth forever
+        for _ in 0..100 {
+            let (payouts, _) = distribute(self, exit, &converted);
+
+            let better = (0..self.preferred.len()).find(|i| {
+                let mut flipped = converted.clone();
+                flipped[*i] = !flipped[*i];
+                let (flipped_payouts, _) = distribute(self, exit, &flipped);
+                // Ignore rounding-sized differences
+                flipped_payouts[*i] > payouts[*i] + 0.5
+            });
+
+            match better {
+                Some(i) => converted[i] = !converted[i],
+                None => break,
+            }
+        }
+
+        let (payouts, per_share) = distribute(self, exit, &converted);
+
+        let series = self
+            .preferred
+            .iter()
+            .zip(payouts.iter().zip(converted))
+            .map(|(s, (payout, converted))| SeriesPayout {
+                name: s.name.clone(),
+                converted,
+                payout_cents: payout.round() as i64,
+            })
+            .collect();
+
+        Waterfall {
+            exit_value_cents,
+            series,
+            common_payout_cents: (per_share * (self.common_shares + self.option_pool) as f64)
+                .round() as i64,
+            price_per_common_share_cents: per_share.floor() as i32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn series(name: &str, participating: bool, participation_cap: Option<f64>) -> PreferredSeries {
+        PreferredSeries {
+            name: name.to_string(),
+            shares: 1_000,
+            issue_price_cents: 1_000,
+            liquidation_multiple: 1.0,
+            participating,
+            participation_cap,
+            seniority: 0,
+        }
+    }
+
+    #[test]
+    fn test_waterfall_preference_exceeds_exit() {
+        let cap_table = CapTable::new(1_000, 0, vec![series("A", false, None)], vec![]);
+
+        let waterfall = cap_table.waterfall(500_000);
+
+        assert_eq!(500_000, waterfall.series[0].payout_cents);
+        assert!(!waterfall.series[0].converted);
+        assert_eq!(0, waterfall.price_per_common_share_cents);
+    }
+
+    #[test]
+    fn test_waterfall_non_participating_converts() {
+        let cap_table = CapTable::new(1_000, 0, vec![series("A", false, None)], vec![]);
+
+        let waterfall = cap_table.waterfall(10_000_000);
+
+        assert!(waterfall.series[0].converted);
+        assert_eq!(5_000_000, waterfall.series[0].payout_cents);
+        assert_eq!(5_000, waterfall.price_per_common_share_cents);
+    }
+
+    #[test]
+    fn test_waterfall_participating() {
+        let cap_table = CapTable::new(1_000, 0, vec![series("A", true, None)], vec![]);
+
+        let waterfall = cap_table.waterfall(3_000_000);
+
+        assert_eq!(2_000_000, waterfall.series[0].payout_cents);
+        assert_eq!(1_000, waterfall.price_per_common_share_cents);
+    }
+
+    #[test]
+    fn test_waterfall_participation_cap() {
+        let cap_table = CapTable::new(1_000, 0, vec![series("A", true, Some(2.0))], vec![]);
+
+        // Capped at 2,000,000 until converting pays more
+        let waterfall = cap_table.waterfall(3_000_000);
+        assert!(!waterfall.series[0].converted);
+        assert_eq!(2_000_000, waterfall.series[0].payout_cents);
+        assert_eq!(1_000, waterfall.price_per_common_share_cents);
+
+        let waterfall = cap_table.waterfall(6_000_000);
+        assert!(waterfall.series[0].converted);
+        assert_eq!(3_000_000, waterfall.series[0].payout_cents);
+        assert_eq!(3_000, waterfall.price_per_common_share_cents);
+    }
+}
@@ -0,0 +1,157 @@
+use chrono::{Days, NaiveDate};
+
+use crate::model::vesting::{normalize, DateOutOfRange, VestingAdjustment, VestingEvent};
+
+/// How a leave of absence changes vesting
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LeavePolicy {
+    /// Every event on or after the start of the leave is pushed back by the
+    /// length of the leave
+    Toll,
+
+    /// Vesting pauses during the leave: events during it vest the day after
+    /// it ends, and later events are unchanged
+    Suspend,
+}
+
+/// A leave of absence, from `starts_on` through `ends_on` inclusive
+#[derive(Debug, Clone)]
+pub struct Leave {
+    /// The first day of the leave
+    pub starts_on: NaiveDate,
+
+    /// The last day of the leave
+    pub ends_on: NaiveDate,
+
+    /// How the leave changes vesting
+    pub policy: LeavePolicy,
+}
+
+impl Leave {
+    /// A leave from `starts_on` through `ends_on` under `policy`
+    pub fn new(starts_on: NaiveDate, ends_on: NaiveDate, policy: LeavePolicy) -> Leave {
+        Leave {
+            starts_on,
+            ends_on,
+            policy,
+        }
+    }
+
+    /// Length of the leave in days, counting both the first and last day, or
+    /// None if it ends before it starts
+    pub fn days(&self) -> Option<u64> {
+        u64::try_from((self.ends_on - self.starts_on).num_days())
+            .ok()?
+            .checked_add(1)
+    }
+}
+
+impl VestingAdjustment for Leave {
+    fn adjust<E: VestingEvent>(&self, events: &[E]) -> Result<Vec<E>, DateOutOfRange> {
+        // A leave that ends before it starts changes nothing
+        let Some(days) = self.days() else {
+            return Ok(events.to_vec());
+        };
+
+        let events = match self.policy {
+            LeavePolicy::Toll => events
+                .iter()
+                .map(|event| {
+                    if event.date() >= self.starts_on {
+                        let date = event
+                            .date()
+                            .checked_add_days(Days::new(days))
+                            .ok_or(DateOutOfRange(event.date()))?;
+                        Ok(event.with(date, event.number()))
+                    } else {
+                        Ok(event.clone())
+                    }
+                })
+                .collect::<Result<Vec<E>, DateOutOfRange>>()?,
+            LeavePolicy::Suspend => events
+                .iter()
+                .map(|event| {
+                    if event.date() >= self.starts_on && event.date() <= self.ends_on {
+                        let date = self
+                            .ends_on
+                            .succ_opt()
+                            .ok_or(DateOutOfRange(event.date()))?;
+                        Ok(event.with(date, event.number()))
+                    } else {
+                        Ok(event.clone())
+                    }
+                })
+                .collect::<Result<Vec<E>, DateOutOfRange>>()?,
+        };
+
+        Ok(normalize(events))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::rsu::RestrictedStockUnitVestingEvent;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn events() -> Vec<RestrictedStockUnitVestingEvent> {
+        vec![
+            RestrictedStockUnitVestingEvent::new(date(2024, 1, 1), 100),
+            RestrictedStockUnitVestingEvent::new(date(2024, 4, 1), 100),
+            RestrictedStockUnitVestingEvent::new(date(2024, 7, 1), 100),
+        ]
+    }
+
+    fn dates(events: &[RestrictedStockUnitVestingEvent]) -> Vec<NaiveDate> {
+        events.iter().map(|event| event.date).collect()
+    }
+
+    #[test]
+    fn test_toll() {
+        let leave = Leave::new(date(2024, 3, 1), date(2024, 3, 10), LeavePolicy::Toll);
+
+        assert_eq!(
+            vec![date(2024, 1, 1), date(2024, 4, 11), date(2024, 7, 11)],
+            dates(&leave.adjust(&events()).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_suspend() {
+        let leave = Leave::new(date(2024, 3, 1), date(2024, 4, 30), LeavePolicy::Suspend);
+        let adjusted = leave.adjust(&events()).unwrap();
+
+        assert_eq!(
+            vec![date(2024, 1, 1), date(2024, 5, 1), date(2024, 7, 1)],
+            dates(&adjusted)
+        );
+
+        // Nothing is forfeited
+        assert_eq!(300, adjusted.iter().map(|event| event.number).sum::<i32>());
+    }
+
+    #[test]
+    fn test_inverted() {
+        let leave = Leave::new(date(2024, 3, 10), date(2024, 3, 1), LeavePolicy::Toll);
+
+        assert_eq!(None, leave.days());
+        assert_eq!(dates(&events()), dates(&leave.adjust(&events()).unwrap()));
+        assert_eq!(
+            Some(1),
+            Leave::new(date(2024, 3, 1), date(2024, 3, 1), LeavePolicy::Toll).days()
+        );
+    }
+
+    #[test]
+    fn test_past_last_date() {
+        let leave = Leave::new(date(2024, 3, 1), NaiveDate::MAX, LeavePolicy::Suspend);
+
+        assert_eq!(
+            Err(DateOutOfRange(date(2024, 4, 1))),
+            leave.adjust(&events()).map(|events| dates(&events))
+        );
+    }
+}
@@ -1,3 +1,29 @@
+/// Vesting acceleration on a change of control
+pub mod acceleration;
+
+/// Shares outstanding and how exit proceeds are paid out
+pub mod cap_table;
+
+/// Leaves of absence and their effect on vesting
+pub mod leave;
+
+/// Stock option grants
 pub mod option;
+
+/// Part-time work and its effect on vesting
+pub mod part_time;
+
+/// The preferred stock price over time
 pub mod psp;
+
+/// Restricted stock unit grants
 pub mod rsu;
+
+/// Hypothetical prices, acquisitions and departures
+pub mod scenario;
+
+/// Trading days and the price shares are valued at when they vest
+pub mod trading;
+
+/// Vesting events and adjustments to them
+pub mod vesting;
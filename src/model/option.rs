@@ -1,11 +1,23 @@
-use chrono::NaiveDate;
+use chrono::{Days, Months, NaiveDate};
 
+use crate::model::{
+    acceleration::Acceleration,
+    trading::VestPriceRule,
+    vesting::{self, VestingEvent},
+};
+
+/// The number of options granted and their exercise price, in cents
+#[derive(Debug, Clone, PartialEq)]
 pub struct OptionGrantValue {
+    /// The price paid to exercise each option, in cents
     pub exercise_price: i32,
+
+    /// Number of options granted
     pub number: i32,
 }
 
 impl OptionGrantValue {
+    /// `number` options exercisable at `exercise_price` cents each
     pub fn new(exercise_price: i32, number: i32) -> OptionGrantValue {
         OptionGrantValue {
             exercise_price,
@@ -14,24 +26,49 @@ impl OptionGrantValue {
     }
 }
 
-#[derive(Clone)]
+/// Options vesting on a date
+#[derive(Debug, Clone, PartialEq)]
 pub struct OptionGrantVestingEvent {
+    /// The day the options vest
     pub date: NaiveDate,
+
+    /// Number of options vesting
     pub number: i32,
 }
 
 impl OptionGrantVestingEvent {
+    /// `number` options vesting on `date`
     pub fn new(date: NaiveDate, number: i32) -> OptionGrantVestingEvent {
         OptionGrantVestingEvent { date, number }
     }
 }
 
+impl VestingEvent for OptionGrantVestingEvent {
+    fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    fn number(&self) -> i32 {
+        self.number
+    }
+
+    fn with(&self, date: NaiveDate, number: i32) -> OptionGrantVestingEvent {
+        OptionGrantVestingEvent::new(date, number)
+    }
+}
+
+/// When vesting commences and each vesting event, in date order
+#[derive(Debug, Clone, PartialEq)]
 pub struct OptionGrantVestingSchedule {
+    /// The day vesting starts counting from
     pub commences_on: NaiveDate,
+
+    /// Vesting events, in date order
     pub events: Vec<OptionGrantVestingEvent>,
 }
 
 impl OptionGrantVestingSchedule {
+    /// A schedule with `events` sorted by date
     pub fn new(
         commences_on: NaiveDate,
         events: Vec<OptionGrantVestingEvent>,
@@ -45,25 +82,149 @@ impl OptionGrantVestingSchedule {
     }
 }
 
+/// How long vested options can be exercised for after leaving the company
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExercisePeriod {
+    /// A number of days
+    Days(u32),
+
+    /// A number of months
+    Months(u32),
+}
+
+impl Default for ExercisePeriod {
+    fn default() -> Self {
+        ExercisePeriod::Days(90)
+    }
+}
+
+impl ExercisePeriod {
+    /// The last day of the period when it starts the day after `date`, or
+    /// `None` if the period ends after the last date that can be represented
+    pub fn after(&self, date: &NaiveDate) -> Option<NaiveDate> {
+        match self {
+            ExercisePeriod::Days(days) => date.checked_add_days(Days::new(*days as u64)),
+            ExercisePeriod::Months(months) => date.checked_add_months(Months::new(*months)),
+        }
+    }
+}
+
+/// A grant of stock options
+#[derive(Debug, Clone, PartialEq)]
 pub struct OptionGrant {
+    /// Name of the grant, unique in its portfolio
     pub name: String,
+
+    /// The day the options were granted
     pub granted_on: NaiveDate,
+
+    /// Number of options and their exercise price
     pub value: OptionGrantValue,
+
+    /// When the options vest
     pub vesting_schedule: OptionGrantVestingSchedule,
+
+    /// How long vested options can be exercised for after leaving
+    pub post_termination_exercise_period: ExercisePeriod,
+
+    /// The last day options can be exercised, even without leaving
+    pub expires_on: NaiveDate,
+
+    /// Acceleration terms on a change of control
+    pub acceleration: Option<Acceleration>,
+
+    /// The price vesting shares are valued at
+    pub vest_price: VestPriceRule,
+
+    /// When the holder left the company, forfeiting any unvested options
+    pub terminated_on: Option<NaiveDate>,
 }
 
 impl OptionGrant {
+    /// A grant valued at the price in effect on each vest date, held until
+    /// it expires. See `with_vest_price` and `terminate`.
     pub fn new(
         name: String,
         granted_on: NaiveDate,
         value: OptionGrantValue,
         vesting_schedule: OptionGrantVestingSchedule,
+        post_termination_exercise_period: ExercisePeriod,
+        expires_on: NaiveDate,
+        acceleration: Option<Acceleration>,
     ) -> OptionGrant {
         OptionGrant {
             name,
             granted_on,
             value,
             vesting_schedule,
+            post_termination_exercise_period,
+            expires_on,
+            acceleration,
+            vest_price: VestPriceRule::default(),
+            terminated_on: None,
         }
     }
+
+    /// The day vesting ends: the last vesting event, or leaving the company
+    pub fn vesting_ends_on(&self) -> NaiveDate {
+        self.terminated_on
+            .or_else(|| self.vesting_schedule.events.last().map(|event| event.date))
+            .unwrap_or(self.vesting_schedule.commences_on)
+    }
+
+    /// This grant with vesting shares valued at `vest_price`
+    pub fn with_vest_price(&self, vest_price: VestPriceRule) -> OptionGrant {
+        let mut grant = self.clone();
+        grant.vest_price = vest_price;
+        grant
+    }
+
+    /// This grant with its vesting events replaced by `events`
+    pub fn with_vesting_events(&self, events: Vec<OptionGrantVestingEvent>) -> OptionGrant {
+        let mut grant = self.clone();
+        // Shares added or removed from the schedule are added to or removed
+        // from the grant
+        let change: i32 = events.iter().map(|event| event.number).sum::<i32>()
+            - self
+                .vesting_schedule
+                .events
+                .iter()
+                .map(|event| event.number)
+                .sum::<i32>();
+        grant.value.number += change;
+
+        grant.vesting_schedule.events = events;
+        grant
+    }
+
+    /// This grant after the company is acquired on `acquired_on`, and the
+    /// holder optionally leaves on `terminated_on`, with any acceleration
+    /// applied. Leaving is not applied; see `terminate`.
+    pub fn acquire(&self, acquired_on: NaiveDate, terminated_on: Option<NaiveDate>) -> OptionGrant {
+        let mut grant = self.clone();
+        if let Some(acceleration) = &self.acceleration {
+            if let Some(on) = acceleration.triggered_on(acquired_on, terminated_on) {
+                grant.vesting_schedule.events =
+                    acceleration.accelerate(&self.vesting_schedule.events, on);
+            }
+        }
+        grant
+    }
+
+    /// This grant after leaving the company on `date`. Vesting events after
+    /// `date` never happen.
+    pub fn terminate(&self, date: NaiveDate) -> OptionGrant {
+        let mut grant = self.clone();
+        grant.vesting_schedule.events = vesting::terminate(&self.vesting_schedule.events, date);
+        grant.terminated_on = Some(date);
+        grant
+    }
+
+    /// The last day vested options can be exercised: when the options expire,
+    /// or the end of the post-termination exercise period if that is sooner
+    pub fn exercisable_until(&self) -> NaiveDate {
+        self.terminated_on
+            .and_then(|date| self.post_termination_exercise_period.after(&date))
+            .map_or(self.expires_on, |date| date.min(self.expires_on))
+    }
 }
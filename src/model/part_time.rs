@@ -0,0 +1,184 @@
+use chrono::NaiveDate;
+
+use crate::model::vesting::{normalize, DateOutOfRange, VestingAdjustment, VestingEvent};
+
+/// What happens to shares that do not vest while working part time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PartTimePolicy {
+    /// Shares that do not vest during the interval vest with the first event
+    /// after it
+    Defer,
+
+    /// Shares that do not vest during the interval are forfeited
+    Forfeit,
+}
+
+/// Working a percentage of full time from `starts_on` through `ends_on`
+/// inclusive, which pro-rates the events vesting in that interval
+#[derive(Debug, Clone)]
+pub struct PartTime {
+    /// The first day of part-time work
+    pub starts_on: NaiveDate,
+
+    /// The last day of part-time work
+    pub ends_on: NaiveDate,
+
+    /// Percentage of full time worked, and of each event that vests
+    pub percentage: f64,
+
+    /// What happens to the shares that do not vest
+    pub policy: PartTimePolicy,
+}
+
+impl PartTime {
+    /// Working `percentage` of full time from `starts_on` through `ends_on`
+    pub fn new(
+        starts_on: NaiveDate,
+        ends_on: NaiveDate,
+        percentage: f64,
+        policy: PartTimePolicy,
+    ) -> PartTime {
+        PartTime {
+            starts_on,
+            ends_on,
+            percentage,
+            policy,
+        }
+    }
+
+    fn contains(&self, date: &NaiveDate) -> bool {
+        &self.starts_on <= date && date <= &self.ends_on
+    }
+}
+
+impl VestingAdjustment for PartTime {
+    fn adjust<E: VestingEvent>(&self, events: &[E]) -> Result<Vec<E>, DateOutOfRange> {
+        let mut remainder = 0;
+        let mut adjusted: Vec<E> = events
+            .iter()
+            .map(|event| {
+                if self.contains(&event.date()) {
+                    let number = (event.number() as f64 * self.percentage / 100.0).floor() as i32;
+                    remainder += event.number() - number;
+                    event.with(event.date(), number)
+                } else {
+                    event.clone()
+                }
+            })
+            .collect();
+
+        if self.policy == PartTimePolicy::Defer && remainder > 0 {
+            match adjusted
+                .iter_mut()
+                .filter(|event| event.date() > self.ends_on)
+                .min_by_key(|event| event.date())
+            {
+                Some(next) => *next = next.with(next.date(), next.number() + remainder),
+                None => {
+                    if let Some(event) = events.first() {
+                        let date = self
+                            .ends_on
+                            .succ_opt()
+                            .ok_or(DateOutOfRange(self.ends_on))?;
+                        adjusted.push(event.with(date, remainder));
+                    }
+                }
+            }
+        }
+
+        Ok(normalize(adjusted))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::rsu::RestrictedStockUnitVestingEvent;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn events() -> Vec<RestrictedStockUnitVestingEvent> {
+        vec![
+            RestrictedStockUnitVestingEvent::new(date(2024, 1, 1), 100),
+            RestrictedStockUnitVestingEvent::new(date(2024, 4, 1), 100),
+            RestrictedStockUnitVestingEvent::new(date(2024, 7, 1), 100),
+        ]
+    }
+
+    fn vests(events: &[RestrictedStockUnitVestingEvent]) -> Vec<(NaiveDate, i32)> {
+        events
+            .iter()
+            .map(|event| (event.date, event.number))
+            .collect()
+    }
+
+    #[test]
+    fn test_defer() {
+        let part_time = PartTime::new(
+            date(2024, 1, 1),
+            date(2024, 4, 30),
+            60.0,
+            PartTimePolicy::Defer,
+        );
+        assert_eq!(
+            vec![
+                (date(2024, 1, 1), 60),
+                (date(2024, 4, 1), 60),
+                (date(2024, 7, 1), 180)
+            ],
+            vests(&part_time.adjust(&events()).unwrap())
+        );
+
+        // Without a later event the remainder vests the day after the interval
+        let part_time = PartTime::new(
+            date(2024, 6, 1),
+            date(2024, 12, 31),
+            60.0,
+            PartTimePolicy::Defer,
+        );
+        assert_eq!(
+            vec![
+                (date(2024, 1, 1), 100),
+                (date(2024, 4, 1), 100),
+                (date(2024, 7, 1), 60),
+                (date(2025, 1, 1), 40)
+            ],
+            vests(&part_time.adjust(&events()).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_forfeit() {
+        let part_time = PartTime::new(
+            date(2024, 1, 1),
+            date(2024, 4, 30),
+            60.0,
+            PartTimePolicy::Forfeit,
+        );
+        assert_eq!(
+            vec![
+                (date(2024, 1, 1), 60),
+                (date(2024, 4, 1), 60),
+                (date(2024, 7, 1), 100)
+            ],
+            vests(&part_time.adjust(&events()).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_defer_past_last_date() {
+        let part_time = PartTime::new(
+            date(2024, 1, 1),
+            NaiveDate::MAX,
+            60.0,
+            PartTimePolicy::Defer,
+        );
+
+        assert_eq!(
+            Err(DateOutOfRange(NaiveDate::MAX)),
+            part_time.adjust(&events()).map(|events| vests(&events))
+        );
+    }
+}
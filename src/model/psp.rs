@@ -1,37 +1,245 @@
-use chrono::NaiveDate;
+use std::num::NonZeroU32;
 
-#[derive(Debug, Clone)]
+use chrono::{Months, NaiveDate};
+
+use crate::model::trading::{TradingCalendar, VestPriceRule};
+
+/// The price per share, in cents, from a date on
+#[derive(Debug, Clone, PartialEq)]
 pub struct PreferredStockPriceValuation {
     date: NaiveDate,
     value_cents: i32,
 }
 
 impl PreferredStockPriceValuation {
+    /// A price of `value_cents` per share from `date` on
     pub fn new(date: NaiveDate, value_cents: i32) -> PreferredStockPriceValuation {
         PreferredStockPriceValuation { date, value_cents }
     }
+
+    /// The day the price takes effect
+    pub fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    /// The price per share, in cents
+    pub fn value_cents(&self) -> i32 {
+        self.value_cents
+    }
 }
 
-#[derive(Debug)]
+/// The preferred stock price over time, from valuations and daily closes
+#[derive(Debug, Clone, PartialEq)]
 pub struct PreferredStockPrice {
     values: Vec<PreferredStockPriceValuation>,
+
+    /// Days with a closing price, for prices relative to trading days
+    calendar: TradingCalendar,
 }
 
 impl PreferredStockPrice {
+    /// A price series from `values`, in any order, trading every weekday
     pub fn new(values: Vec<PreferredStockPriceValuation>) -> PreferredStockPrice {
         let mut values = values.clone();
         values.sort_by(|a, b| a.date.cmp(&b.date));
 
-        PreferredStockPrice { values }
+        PreferredStockPrice {
+            values,
+            calendar: TradingCalendar::default(),
+        }
+    }
+
+    /// A copy of this price series using `calendar` for trading days
+    pub fn with_calendar(&self, calendar: TradingCalendar) -> PreferredStockPrice {
+        PreferredStockPrice {
+            values: self.values.clone(),
+            calendar,
+        }
     }
 
-    pub fn value_on(&self, date: &NaiveDate) -> i32 {
-        // The first valuation after `date`
-        self.values
+    /// The latest valuation, if there are any
+    pub fn last_valuation(&self) -> Option<&PreferredStockPriceValuation> {
+        self.values.last()
+    }
+
+    /// A copy of this price series with `valuations` added. Added valuations
+    /// replace existing valuations on the same date.
+    pub fn with_valuations(
+        &self,
+        valuations: Vec<PreferredStockPriceValuation>,
+    ) -> PreferredStockPrice {
+        let mut values: Vec<PreferredStockPriceValuation> = self
+            .values
             .iter()
-            .take_while(|valuation| &valuation.date <= date)
-            .last()
-            .unwrap_or_else(|| panic!("No valuation found for {date}"))
-            .value_cents
+            .filter(|existing| !valuations.iter().any(|v| v.date == existing.date))
+            .cloned()
+            .collect();
+        values.extend(valuations);
+
+        PreferredStockPrice::new(values).with_calendar(self.calendar.clone())
+    }
+
+    /// A copy of this price series extended from its last valuation until
+    /// `until`, adding a valuation every `every_months` months that grows at
+    /// `annual_rate` (e.g. 0.1 for 10%) compounded annually.
+    pub fn with_growth(
+        &self,
+        annual_rate: f64,
+        until: NaiveDate,
+        every_months: NonZeroU32,
+    ) -> PreferredStockPrice {
+        let Some(last) = self.values.last() else {
+            return self.clone();
+        };
+
+        let mut valuations = Vec::new();
+        let mut step = 1;
+        while let Some((months, date)) = every_months
+            .get()
+            .checked_mul(step)
+            .and_then(|months| Some((months, last.date.checked_add_months(Months::new(months))?)))
+            .filter(|(_, date)| date <= &until)
+        {
+            let years = months as f64 / 12.0;
+            let value_cents = (last.value_cents as f64 * (1.0 + annual_rate).powf(years)) as i32;
+            valuations.push(PreferredStockPriceValuation::new(date, value_cents));
+            step += 1;
+        }
+
+        self.with_valuations(valuations)
+    }
+
+    /// The value of the last valuation on or before `date`, or `None` before
+    /// the first valuation
+    pub fn value_on(&self, date: &NaiveDate) -> Option<i32> {
+        // Values are sorted by date
+        let after = self
+            .values
+            .partition_point(|valuation| &valuation.date <= date);
+        after
+            .checked_sub(1)
+            .map(|last| self.values[last].value_cents)
+    }
+
+    /// The price shares vesting on `date` are valued at under `rule`, or
+    /// `None` before the first valuation. Averages leave out days before the
+    /// first valuation.
+    pub fn vest_price(&self, date: &NaiveDate, rule: VestPriceRule) -> Option<i32> {
+        match rule {
+            VestPriceRule::Close => self.value_on(date),
+            VestPriceRule::PriorClose => self.value_on(&self.calendar.before(date).ok()?),
+            VestPriceRule::Average(days) if days > 0 => {
+                let mut day = self.calendar.on_or_before(date).ok()?;
+                let mut total: i64 = 0;
+                let mut priced_days = 0;
+                for i in 0..days {
+                    if i > 0 {
+                        // No earlier day has a valuation either
+                        let Ok(before) = self.calendar.before(&day) else {
+                            break;
+                        };
+                        day = before;
+                    }
+                    if let Some(value) = self.value_on(&day) {
+                        total += value as i64;
+                        priced_days += 1;
+                    }
+                }
+                (priced_days > 0).then(|| (total as f64 / priced_days as f64).round() as i32)
+            }
+            VestPriceRule::Average(_) => self.value_on(date),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_value_on() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let psp = PreferredStockPrice::new(vec![PreferredStockPriceValuation::new(
+            date(2024, 1, 1),
+            1000,
+        )]);
+
+        assert_eq!(None, psp.value_on(&date(2023, 12, 31)));
+        assert_eq!(Some(1000), psp.value_on(&date(2024, 1, 1)));
+        assert_eq!(
+            None,
+            PreferredStockPrice::new(vec![]).value_on(&date(2024, 1, 1))
+        );
+    }
+
+    #[test]
+    fn test_vest_price() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        // Closes Wednesday to Friday, with Friday 2024-07-05 a holiday
+        let psp = PreferredStockPrice::new(vec![
+            PreferredStockPriceValuation::new(date(2024, 7, 1), 1000),
+            PreferredStockPriceValuation::new(date(2024, 7, 2), 1100),
+            PreferredStockPriceValuation::new(date(2024, 7, 3), 1200),
+            PreferredStockPriceValuation::new(date(2024, 7, 8), 1300),
+        ])
+        .with_calendar(TradingCalendar::new(vec![
+            date(2024, 7, 4),
+            date(2024, 7, 5),
+        ]));
+
+        // Vesting on Monday 2024-07-08
+        let vest_date = date(2024, 7, 8);
+        assert_eq!(Some(1300), psp.vest_price(&vest_date, VestPriceRule::Close));
+        assert_eq!(
+            Some(1200),
+            psp.vest_price(&vest_date, VestPriceRule::PriorClose)
+        );
+        assert_eq!(
+            Some(1250),
+            psp.vest_price(&vest_date, VestPriceRule::Average(2))
+        );
+        assert_eq!(
+            Some(1200),
+            psp.vest_price(&vest_date, VestPriceRule::Average(3))
+        );
+
+        // Vesting on Saturday 2024-07-06 uses Wednesday's close
+        let vest_date = date(2024, 7, 6);
+        assert_eq!(Some(1200), psp.vest_price(&vest_date, VestPriceRule::Close));
+        assert_eq!(
+            Some(1200),
+            psp.vest_price(&vest_date, VestPriceRule::PriorClose)
+        );
+        assert_eq!(
+            Some(1150),
+            psp.vest_price(&vest_date, VestPriceRule::Average(2))
+        );
+
+        // Averages leave out days before the first close
+        let vest_date = date(2024, 7, 2);
+        assert_eq!(
+            Some(1050),
+            psp.vest_price(&vest_date, VestPriceRule::Average(5))
+        );
+        assert_eq!(
+            None,
+            psp.vest_price(&date(2024, 6, 30), VestPriceRule::Average(5))
+        );
+    }
+
+    #[test]
+    fn test_with_growth() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let psp = PreferredStockPrice::new(vec![PreferredStockPriceValuation::new(
+            date(2024, 1, 1),
+            1000,
+        )]);
+
+        let grown = psp.with_growth(1.0, date(2026, 6, 1), NonZeroU32::new(12).unwrap());
+
+        assert_eq!(Some(1000), grown.value_on(&date(2024, 12, 31)));
+        assert_eq!(Some(2000), grown.value_on(&date(2025, 1, 1)));
+        assert_eq!(Some(4000), grown.value_on(&date(2026, 1, 1)));
+        assert_eq!(3, grown.values.len());
     }
 }
@@ -1,37 +1,83 @@
 use chrono::NaiveDate;
 
+use crate::model::{
+    acceleration::Acceleration,
+    trading::VestPriceRule,
+    vesting::{self, VestingEvent},
+};
+
+/// The value granted and the price per unit it was converted at, in cents
+#[derive(Debug, Clone, PartialEq)]
 pub struct RestrictedStockUnitValue {
+    /// The price per unit the grant's value was converted at
     pub grant_price_cents: i32,
+
+    /// The value granted
     pub total_value_cents: i32,
 }
 
 impl RestrictedStockUnitValue {
+    /// A grant worth `total_value_cents`, converted to units at
+    /// `grant_price_cents` each
     pub fn new(grant_price_cents: i32, total_value_cents: i32) -> RestrictedStockUnitValue {
         RestrictedStockUnitValue {
             grant_price_cents,
             total_value_cents,
         }
     }
+
+    /// Number of units granted, as implied by the grant price and total value
+    pub fn units(&self) -> i32 {
+        if self.grant_price_cents == 0 {
+            return 0;
+        }
+        self.total_value_cents / self.grant_price_cents
+    }
 }
 
-#[derive(Clone)]
+/// Units vesting on a date
+#[derive(Debug, Clone, PartialEq)]
 pub struct RestrictedStockUnitVestingEvent {
+    /// The day the units vest
     pub date: NaiveDate,
+
+    /// Number of units vesting
     pub number: i32,
 }
 
 impl RestrictedStockUnitVestingEvent {
+    /// `number` units vesting on `date`
     pub fn new(date: NaiveDate, number: i32) -> RestrictedStockUnitVestingEvent {
         RestrictedStockUnitVestingEvent { date, number }
     }
 }
 
+impl VestingEvent for RestrictedStockUnitVestingEvent {
+    fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    fn number(&self) -> i32 {
+        self.number
+    }
+
+    fn with(&self, date: NaiveDate, number: i32) -> RestrictedStockUnitVestingEvent {
+        RestrictedStockUnitVestingEvent::new(date, number)
+    }
+}
+
+/// When vesting commences and each vesting event, in date order
+#[derive(Debug, Clone, PartialEq)]
 pub struct RestrictedStockUnitVestingSchedule {
+    /// The day vesting starts counting from
     pub commences_on: NaiveDate,
+
+    /// Vesting events, in date order
     pub events: Vec<RestrictedStockUnitVestingEvent>,
 }
 
 impl RestrictedStockUnitVestingSchedule {
+    /// A schedule with `events` sorted by date
     pub fn new(
         commences_on: NaiveDate,
         events: Vec<RestrictedStockUnitVestingEvent>,
@@ -45,28 +91,105 @@ impl RestrictedStockUnitVestingSchedule {
     }
 }
 
+/// A grant of restricted stock units
+#[derive(Debug, Clone, PartialEq)]
 pub struct RestrictedStockUnitGrant {
+    /// Name of the grant, unique in its portfolio
     pub name: String,
+
+    /// The day the units were granted
     pub granted_on: NaiveDate,
+
+    /// The value granted and the price it was converted to units at
     pub value: RestrictedStockUnitValue,
+
+    /// When the units vest
     pub vesting_schedule: RestrictedStockUnitVestingSchedule,
+
+    /// Acceleration terms on a change of control
+    pub acceleration: Option<Acceleration>,
+
+    /// The price vesting shares are valued at
+    pub vest_price: VestPriceRule,
+
+    /// When the holder left the company, forfeiting any unvested units
+    pub terminated_on: Option<NaiveDate>,
 }
 
 impl RestrictedStockUnitGrant {
+    /// A grant valued at the price in effect on each vest date, held through
+    /// every event. See `with_vest_price` and `terminate`.
     pub fn new(
         name: String,
         granted_on: NaiveDate,
         value: RestrictedStockUnitValue,
         vesting_schedule: RestrictedStockUnitVestingSchedule,
+        acceleration: Option<Acceleration>,
     ) -> RestrictedStockUnitGrant {
         RestrictedStockUnitGrant {
             name,
             granted_on,
             value,
             vesting_schedule,
+            acceleration,
+            vest_price: VestPriceRule::default(),
+            terminated_on: None,
         }
     }
 
+    /// The day vesting ends: the last vesting event, or leaving the company
+    pub fn vesting_ends_on(&self) -> NaiveDate {
+        self.terminated_on
+            .or_else(|| self.vesting_schedule.events.last().map(|event| event.date))
+            .unwrap_or(self.vesting_schedule.commences_on)
+    }
+
+    /// This grant with vesting shares valued at `vest_price`
+    pub fn with_vest_price(&self, vest_price: VestPriceRule) -> RestrictedStockUnitGrant {
+        let mut grant = self.clone();
+        grant.vest_price = vest_price;
+        grant
+    }
+
+    /// This grant with its vesting events replaced by `events`
+    pub fn with_vesting_events(
+        &self,
+        events: Vec<RestrictedStockUnitVestingEvent>,
+    ) -> RestrictedStockUnitGrant {
+        let mut grant = self.clone();
+        grant.vesting_schedule.events = events;
+        grant
+    }
+
+    /// This grant after the company is acquired on `acquired_on`, and the
+    /// holder optionally leaves on `terminated_on`, with any acceleration
+    /// applied. Leaving is not applied; see `terminate`.
+    pub fn acquire(
+        &self,
+        acquired_on: NaiveDate,
+        terminated_on: Option<NaiveDate>,
+    ) -> RestrictedStockUnitGrant {
+        let mut grant = self.clone();
+        if let Some(acceleration) = &self.acceleration {
+            if let Some(on) = acceleration.triggered_on(acquired_on, terminated_on) {
+                grant.vesting_schedule.events =
+                    acceleration.accelerate(&self.vesting_schedule.events, on);
+            }
+        }
+        grant
+    }
+
+    /// This grant after leaving the company on `date`. Vesting events after
+    /// `date` never happen.
+    pub fn terminate(&self, date: NaiveDate) -> RestrictedStockUnitGrant {
+        let mut grant = self.clone();
+        grant.vesting_schedule.events = vesting::terminate(&self.vesting_schedule.events, date);
+        grant.terminated_on = Some(date);
+        grant
+    }
+
+    /// Number of units in the vesting schedule, which can differ from the
+    /// units implied by the grant's value after adjustments
     pub fn actual_total_units(&self) -> i32 {
         self.vesting_schedule
             .events
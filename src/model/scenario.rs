@@ -0,0 +1,37 @@
+use chrono::NaiveDate;
+
+use crate::model::psp::PreferredStockPrice;
+
+/// A named, hypothetical preferred stock price path, with an optional
+/// acquisition of the company and departure of the holder
+pub struct Scenario {
+    /// Name of the scenario
+    pub name: String,
+
+    /// The price path the scenario values grants at
+    pub psp: PreferredStockPrice,
+
+    /// When the company is acquired, accelerating vesting
+    pub acquired_on: Option<NaiveDate>,
+
+    /// When the holder leaves the company
+    pub terminated_on: Option<NaiveDate>,
+}
+
+impl Scenario {
+    /// A scenario valuing grants at `psp`, with optional acquisition and
+    /// departure dates
+    pub fn new(
+        name: String,
+        psp: PreferredStockPrice,
+        acquired_on: Option<NaiveDate>,
+        terminated_on: Option<NaiveDate>,
+    ) -> Scenario {
+        Scenario {
+            name,
+            psp,
+            acquired_on,
+            terminated_on,
+        }
+    }
+}
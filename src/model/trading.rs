@@ -0,0 +1,87 @@
+use std::collections::BTreeSet;
+
+use chrono::{Datelike, NaiveDate, Weekday};
+
+/// There is no trading day before the date that can be represented
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("no trading day before {0}")]
+pub struct NoTradingDay(pub NaiveDate);
+
+/// Days the market is open: weekdays that are not holidays
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TradingCalendar {
+    holidays: BTreeSet<NaiveDate>,
+}
+
+impl TradingCalendar {
+    /// A calendar closed on weekends and `holidays`
+    pub fn new(holidays: Vec<NaiveDate>) -> TradingCalendar {
+        TradingCalendar {
+            holidays: holidays.into_iter().collect(),
+        }
+    }
+
+    /// Whether the market is open on `date`
+    pub fn is_trading_day(&self, date: &NaiveDate) -> bool {
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !self.holidays.contains(date)
+    }
+
+    /// `date` if it is a trading day, or else the last trading day before it
+    pub fn on_or_before(&self, date: &NaiveDate) -> Result<NaiveDate, NoTradingDay> {
+        let mut day = *date;
+        while !self.is_trading_day(&day) {
+            day = day.pred_opt().ok_or(NoTradingDay(*date))?;
+        }
+        Ok(day)
+    }
+
+    /// The last trading day before `date`
+    pub fn before(&self, date: &NaiveDate) -> Result<NaiveDate, NoTradingDay> {
+        self.on_or_before(&date.pred_opt().ok_or(NoTradingDay(*date))?)
+    }
+}
+
+/// Which price a grant's vesting shares are valued at
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum VestPriceRule {
+    /// The price in effect on the vest date
+    #[default]
+    Close,
+
+    /// The close of the last trading day before the vest date
+    PriorClose,
+
+    /// The average close of this many trading days, ending on the vest date
+    /// or the last trading day before it
+    Average(u32),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_trading_days() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        // Monday 2024-09-02 is Labor Day
+        let calendar = TradingCalendar::new(vec![date(2024, 9, 2)]);
+
+        assert!(calendar.is_trading_day(&date(2024, 8, 30)));
+        assert!(!calendar.is_trading_day(&date(2024, 8, 31)));
+        assert!(!calendar.is_trading_day(&date(2024, 9, 2)));
+
+        assert_eq!(
+            Ok(date(2024, 8, 30)),
+            calendar.on_or_before(&date(2024, 9, 2))
+        );
+        assert_eq!(
+            Ok(date(2024, 9, 3)),
+            calendar.on_or_before(&date(2024, 9, 3))
+        );
+        assert_eq!(Ok(date(2024, 8, 30)), calendar.before(&date(2024, 9, 3)));
+        assert_eq!(
+            Err(NoTradingDay(NaiveDate::MIN)),
+            calendar.before(&NaiveDate::MIN)
+        );
+    }
+}
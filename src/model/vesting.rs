@@ -0,0 +1,65 @@
+use chrono::NaiveDate;
+
+/// A number of shares vesting on a date, shared by option and RSU vesting
+/// events so schedules can be transformed the same way
+pub trait VestingEvent: Clone {
+    /// The day the shares vest
+    fn date(&self) -> NaiveDate;
+
+    /// Number of shares vesting
+    fn number(&self) -> i32;
+
+    /// A copy of this event with a different date and number
+    fn with(&self, date: NaiveDate, number: i32) -> Self;
+}
+
+/// Combine events on the same date and drop empty events, keeping date order
+pub fn normalize<E: VestingEvent>(events: Vec<E>) -> Vec<E> {
+    let mut events = events;
+    events.sort_by_key(|event| event.date());
+
+    let mut normalized: Vec<E> = Vec::new();
+    for event in events {
+        match normalized.last_mut() {
+            Some(last) if last.date() == event.date() => {
+                *last = last.with(last.date(), last.number() + event.number());
+            }
+            _ => normalized.push(event),
+        }
+    }
+
+    normalized.retain(|event| event.number() != 0);
+    normalized
+}
+
+/// The events that still happen after leaving on `date`: those on or before
+/// it. Later events never happen.
+pub fn terminate<E: VestingEvent>(events: &[E], date: NaiveDate) -> Vec<E> {
+    events
+        .iter()
+        .filter(|event| event.date() <= date)
+        .cloned()
+        .collect()
+}
+
+/// An adjustment would move vesting on the date past the last date that can
+/// be represented
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("vesting on {0} cannot be moved past {max}", max = NaiveDate::MAX)]
+pub struct DateOutOfRange(pub NaiveDate);
+
+/// A change to vesting schedules, such as a leave of absence, that applies to
+/// every grant
+pub trait VestingAdjustment {
+    /// `events` after this adjustment
+    fn adjust<E: VestingEvent>(&self, events: &[E]) -> Result<Vec<E>, DateOutOfRange>;
+}
+
+/// Each adjustment applied in turn
+impl<A: VestingAdjustment> VestingAdjustment for [A] {
+    fn adjust<E: VestingEvent>(&self, events: &[E]) -> Result<Vec<E>, DateOutOfRange> {
+        self.iter().try_fold(events.to_vec(), |events, adjustment| {
+            adjustment.adjust(&events)
+        })
+    }
+}
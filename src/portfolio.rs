@@ -0,0 +1,292 @@
+use std::path::Path;
+
+use chrono::NaiveDate;
+
+use crate::{
+    dto,
+    model::{
+        cap_table::CapTable,
+        option::OptionGrant,
+        psp::PreferredStockPrice,
+        rsu::RestrictedStockUnitGrant,
+        vesting::{DateOutOfRange, VestingAdjustment},
+    },
+    report::adjustment::AdjustmentReport,
+};
+
+/// The grants and prices of a portfolio, ready to report on. Planned funding
+/// rounds extend the price, and leaves of absence and part-time work are
+/// applied to vesting.
+pub struct Portfolio {
+    /// Where the portfolio was loaded from, for files read later such as
+    /// scenarios
+    pub files: dto::PortfolioFiles,
+
+    /// The price from the price files, extended with planned funding rounds
+    pub psp: PreferredStockPrice,
+
+    /// The price from the price files alone, which simulations and scenarios
+    /// start from
+    pub known_psp: PreferredStockPrice,
+
+    /// Option grants, with the portfolio's adjustments applied
+    pub option_grants: Vec<OptionGrant>,
+
+    /// RSU grants, with the portfolio's adjustments applied
+    pub rsu_grants: Vec<RestrictedStockUnitGrant>,
+
+    /// The cap table, if the portfolio has one
+    pub cap_table: Option<CapTable>,
+
+    /// The vesting events changed by each adjustment applied, in order
+    pub adjustments: Vec<AdjustmentReport>,
+}
+
+/// The grants and prices of a portfolio under one of its scenarios
+pub struct ScenarioPortfolio {
+    /// Name of the scenario
+    pub name: String,
+
+    /// The scenario's price path
+    pub psp: PreferredStockPrice,
+
+    /// Option grants after the scenario's acquisition and departure
+    pub option_grants: Vec<OptionGrant>,
+
+    /// RSU grants after the scenario's acquisition and departure
+    pub rsu_grants: Vec<RestrictedStockUnitGrant>,
+}
+
+impl Portfolio {
+    /// Load the portfolio at `path`, a directory of files or a single
+    /// portfolio.yaml or portfolio.toml. When `strict`, keys that do not match
+    /// any field are an error rather than ignored.
+    pub fn load(path: &Path, strict: bool) -> dto::Result<Portfolio> {
+        if !path.exists() {
+            return Err(dto::LoadError::PortfolioNotFound(path.to_path_buf()).into());
+        }
+
+        let files = dto::PortfolioFiles::open(path, strict)?;
+        let known_psp = dto::load_psp(&files)?;
+        let option_grants = dto::load_option_grants(&files)?;
+        let rsu_grants = dto::load_rsu_grants(&files)?;
+
+        // Every file is optional, but grants cannot be valued without prices
+        let has_grants = !option_grants.is_empty() || !rsu_grants.is_empty();
+        if has_grants && known_psp.last_valuation().is_none() {
+            return Err(dto::LoadError::PricesMissing(path.to_path_buf()).into());
+        }
+
+        let cap_table = dto::load_cap_table(&files)?;
+
+        // Planned funding rounds set the price after the last known valuation
+        let psp = match &cap_table {
+            Some(cap_table) => cap_table.extend_price(&known_psp),
+            None => known_psp.clone(),
+        };
+
+        let leaves = dto::load_leaves(&files)?;
+        let part_time = dto::load_part_time(&files)?;
+
+        let mut portfolio = Portfolio {
+            files,
+            psp,
+            known_psp,
+            option_grants,
+            rsu_grants,
+            cap_table,
+            adjustments: Vec::new(),
+        };
+
+        if !leaves.is_empty() {
+            portfolio =
+                portfolio.adjust_vesting("Vesting adjusted for leaves of absence", &leaves[..])?;
+        }
+
+        if !part_time.is_empty() {
+            portfolio =
+                portfolio.adjust_vesting("Vesting adjusted for part-time work", &part_time[..])?;
+        }
+
+        Ok(portfolio)
+    }
+
+    /// The portfolio with `adjustment` applied to every grant, recording the
+    /// vesting events that changed under `title`
+    pub fn adjust_vesting(
+        self,
+        title: &str,
+        adjustment: &(impl VestingAdjustment + ?Sized),
+    ) -> Result<Portfolio, DateOutOfRange> {
+        let option_grants = self
+            .option_grants
+            .iter()
+            .map(|grant| {
+                Ok(grant.with_vesting_events(adjustment.adjust(&grant.vesting_schedule.events)?))
+            })
+            .collect::<Result<Vec<_>, DateOutOfRange>>()?;
+        let rsu_grants = self
+            .rsu_grants
+            .iter()
+            .map(|grant| {
+                Ok(grant.with_vesting_events(adjustment.adjust(&grant.vesting_schedule.events)?))
+            })
+            .collect::<Result<Vec<_>, DateOutOfRange>>()?;
+
+        let mut adjustments = self.adjustments;
+        adjustments.push(AdjustmentReport::new(
+            title,
+            &self.option_grants,
+            &option_grants,
+            &self.rsu_grants,
+            &rsu_grants,
+        ));
+
+        Ok(Portfolio {
+            option_grants,
+            rsu_grants,
+            adjustments,
+            ..self
+        })
+    }
+
+    /// The portfolio after leaving the company on `date`
+    pub fn terminate(self, date: NaiveDate) -> Portfolio {
+        Portfolio {
+            option_grants: self
+                .option_grants
+                .iter()
+                .map(|grant| grant.terminate(date))
+                .collect(),
+            rsu_grants: self
+                .rsu_grants
+                .iter()
+                .map(|grant| grant.terminate(date))
+                .collect(),
+            ..self
+        }
+    }
+
+    /// The portfolio under each scenario in its scenarios file. Scenarios can
+    /// change the known price, and acquire the company or leave it.
+    pub fn scenarios(&self) -> dto::Result<Vec<ScenarioPortfolio>> {
+        Ok(dto::load_scenarios(&self.files, &self.known_psp)?
+            .into_iter()
+            .map(|scenario| {
+                let mut option_grants = self.option_grants.clone();
+                let mut rsu_grants = self.rsu_grants.clone();
+
+                if let Some(acquired_on) = scenario.acquired_on {
+                    option_grants = option_grants
+                        .iter()
+                        .map(|grant| grant.acquire(acquired_on, scenario.terminated_on))
+                        .collect();
+                    rsu_grants = rsu_grants
+                        .iter()
+                        .map(|grant| grant.acquire(acquired_on, scenario.terminated_on))
+                        .collect();
+                }
+
+                if let Some(terminated_on) = scenario.terminated_on {
+                    option_grants = option_grants
+                        .iter()
+                        .map(|grant| grant.terminate(terminated_on))
+                        .collect();
+                    rsu_grants = rsu_grants
+                        .iter()
+                        .map(|grant| grant.terminate(terminated_on))
+                        .collect();
+                }
+
+                ScenarioPortfolio {
+                    name: scenario.name,
+                    psp: scenario.psp,
+                    option_grants,
+                    rsu_grants,
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::report::{
+        simulate::{Simulation, SimulationOptions},
+        total::Granularity,
+    };
+
+    fn acme() -> Portfolio {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/acme");
+        Portfolio::load(&path, false).unwrap()
+    }
+
+    fn funding_rounds() -> Portfolio {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/funding-rounds.yaml");
+        Portfolio::load(&path, true).unwrap()
+    }
+
+    fn assert_same_models(a: &Portfolio, b: &Portfolio) {
+        assert_eq!(a.known_psp, b.known_psp);
+        assert_eq!(a.psp, b.psp);
+        assert_eq!(a.option_grants, b.option_grants);
+        assert_eq!(a.rsu_grants, b.rsu_grants);
+        assert_eq!(a.cap_table, b.cap_table);
+    }
+
+    #[test]
+    fn test_single_file_loads_like_directory() {
+        let examples = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+
+        let toml = Portfolio::load(&examples.join("acme.toml"), true).unwrap();
+        assert_same_models(&acme(), &toml);
+
+        let temp = tempfile::tempdir().unwrap();
+        let directory = temp.path().join("initech");
+        dto::export::export_portfolio(
+            &examples.join("initech/portfolio.yaml"),
+            Some(&directory),
+            true,
+        )
+        .unwrap();
+        let from_directory = Portfolio::load(&directory, true).unwrap();
+
+        let yaml = Portfolio::load(&examples.join("initech/portfolio.yaml"), true).unwrap();
+        assert_same_models(&yaml, &from_directory);
+    }
+
+    #[test]
+    fn test_planned_rounds_extend_price() {
+        let portfolio = funding_rounds();
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        assert_eq!(Some(810), portfolio.known_psp.value_on(&date(2025, 10, 1)));
+        assert_eq!(Some(2000), portfolio.psp.value_on(&date(2025, 10, 1)));
+        assert_eq!(
+            Some(date(2023, 9, 13)),
+            portfolio.known_psp.last_valuation().map(|v| v.date())
+        );
+    }
+
+    #[test]
+    fn test_simulate_starts_from_known_price() {
+        let portfolio = funding_rounds();
+        let simulation = Simulation::new(
+            &portfolio.known_psp,
+            &portfolio.option_grants,
+            &portfolio.rsu_grants,
+            SimulationOptions {
+                paths: 50,
+                drift: 0.1,
+                volatility: 0.5,
+                seed: 0,
+                until: None,
+                granularity: Granularity::Month,
+            },
+        );
+
+        let last = simulation.items.last().unwrap();
+        assert!(last.total[0] < last.total[2]);
+    }
+}
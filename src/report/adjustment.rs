@@ -0,0 +1,128 @@
+use std::io::Write;
+
+use chrono::NaiveDate;
+
+use crate::{
+    model::{option::OptionGrant, rsu::RestrictedStockUnitGrant},
+    report::{event_changes, print_event_changes},
+};
+
+/// The vesting events of a grant that an adjustment changed
+pub struct GrantAdjustment {
+    /// Name of the grant
+    pub name: String,
+
+    /// "Option" or "RSU"
+    pub kind: &'static str,
+
+    /// Date and number of each event no longer vesting
+    pub removed: Vec<(NaiveDate, i32)>,
+
+    /// Date and number of each event vesting instead
+    pub added: Vec<(NaiveDate, i32)>,
+}
+
+/// Vesting events that changed when grants were adjusted, e.g. for a leave of
+/// absence
+pub struct AdjustmentReport {
+    /// What adjusted the grants, e.g. the leave of absence
+    pub title: String,
+
+    /// The grants whose vesting changed
+    pub grants: Vec<GrantAdjustment>,
+}
+
+fn adjustment(
+    name: &str,
+    kind: &'static str,
+    original: Vec<(NaiveDate, i32)>,
+    adjusted: Vec<(NaiveDate, i32)>,
+) -> Option<GrantAdjustment> {
+    let (removed, added) = event_changes(&original, &adjusted);
+
+    if removed.is_empty() && added.is_empty() {
+        None
+    } else {
+        Some(GrantAdjustment {
+            name: name.to_string(),
+            kind,
+            removed,
+            added,
+        })
+    }
+}
+
+impl AdjustmentReport {
+    /// The vesting events that differ between the original and adjusted
+    /// grants, matched by position
+    pub fn new(
+        title: &str,
+        original_option_grants: &[OptionGrant],
+        adjusted_option_grants: &[OptionGrant],
+        original_rsu_grants: &[RestrictedStockUnitGrant],
+        adjusted_rsu_grants: &[RestrictedStockUnitGrant],
+    ) -> AdjustmentReport {
+        let option_events = |grant: &OptionGrant| -> Vec<(NaiveDate, i32)> {
+            grant
+                .vesting_schedule
+                .events
+                .iter()
+                .map(|event| (event.date, event.number))
+                .collect()
+        };
+        let rsu_events = |grant: &RestrictedStockUnitGrant| -> Vec<(NaiveDate, i32)> {
+            grant
+                .vesting_schedule
+                .events
+                .iter()
+                .map(|event| (event.date, event.number))
+                .collect()
+        };
+
+        let options = original_option_grants
+            .iter()
+            .zip(adjusted_option_grants)
+            .filter_map(|(original, adjusted)| {
+                adjustment(
+                    &original.name,
+                    "Option",
+                    option_events(original),
+                    option_events(adjusted),
+                )
+            });
+        let rsus = original_rsu_grants
+            .iter()
+            .zip(adjusted_rsu_grants)
+            .filter_map(|(original, adjusted)| {
+                adjustment(
+                    &original.name,
+                    "RSU",
+                    rsu_events(original),
+                    rsu_events(adjusted),
+                )
+            });
+
+        AdjustmentReport {
+            title: title.to_string(),
+            grants: options.chain(rsus).collect(),
+        }
+    }
+
+    /// Write the removed and added events of each grant
+    pub fn print(&self, out: &mut impl Write) -> std::io::Result<()> {
+        writeln!(out, "{}\n", self.title)?;
+
+        if self.grants.is_empty() {
+            return writeln!(out, "No vesting events changed\n");
+        }
+
+        for grant in &self.grants {
+            writeln!(out, "{} ({})", grant.name, grant.kind)?;
+
+            print_event_changes(out, &grant.removed, &grant.added)?;
+            writeln!(out)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,373 @@
+use std::io::Write;
+
+use chrono::{Datelike, NaiveDate, Utc};
+
+use crate::{
+    model::{option::OptionGrant, psp::PreferredStockPrice, rsu::RestrictedStockUnitGrant},
+    report::{format_currency, slugify},
+};
+
+/// Shares or units of a grant vesting on a date
+pub struct CalendarEvent {
+    /// The day the shares or units vest
+    pub date: NaiveDate,
+
+    /// Name of the grant
+    pub grant_name: String,
+
+    /// Position of the grant in the portfolio, options first, which tells
+    /// apart grants whose names differ only in punctuation or case
+    pub grant_index: usize,
+
+    /// "Option" or "RSU"
+    pub kind: &'static str,
+
+    /// Number of shares or units vesting
+    pub shares: i32,
+
+    /// Value of each share or unit vesting, in cents
+    pub unit_value: i32,
+
+    /// Value of all the shares or units vesting, in cents
+    pub value: i64,
+}
+
+/// Upcoming vesting events across all grants
+pub struct Calendar {
+    /// The events in date order
+    pub events: Vec<CalendarEvent>,
+}
+
+/// Escape a value for use in an iCalendar TEXT property (RFC 5545 3.3.11)
+fn escape_ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Fold a content line so no line is longer than 75 octets (RFC 5545 3.1)
+fn fold_ics_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut line_len = 0;
+    for c in line.chars() {
+        if line_len + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            line_len = 1;
+        }
+        folded.push(c);
+        line_len += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+impl Calendar {
+    /// Collect every vesting event on or after `from`, in date order
+    pub fn new(
+        psp: &PreferredStockPrice,
+        option_grants: &[OptionGrant],
+        rsu_grants: &[RestrictedStockUnitGrant],
+        from: NaiveDate,
+    ) -> Calendar {
+        let mut events: Vec<CalendarEvent> = Vec::new();
+
+        for (grant_index, grant) in option_grants.iter().enumerate() {
+            for event in &grant.vesting_schedule.events {
+                if event.date < from {
+                    continue;
+                }
+                let unit_value = psp
+                    .vest_price(&event.date, grant.vest_price)
+                    .map_or(0, |price| price - grant.value.exercise_price);
+                events.push(CalendarEvent {
+                    date: event.date,
+                    grant_name: grant.name.clone(),
+                    grant_index,
+                    kind: "Option",
+                    shares: event.number,
+                    unit_value,
+                    value: event.number as i64 * unit_value as i64,
+                });
+            }
+        }
+
+        for (grant_index, grant) in rsu_grants.iter().enumerate() {
+            let grant_index = option_grants.len() + grant_index;
+            for event in &grant.vesting_schedule.events {
+                if event.date < from {
+                    continue;
+                }
+                let unit_value = psp.vest_price(&event.date, grant.vest_price).unwrap_or(0);
+                events.push(CalendarEvent {
+                    date: event.date,
+                    grant_name: grant.name.clone(),
+                    grant_index,
+                    kind: "RSU",
+                    shares: event.number,
+                    unit_value,
+                    value: event.number as i64 * unit_value as i64,
+                });
+            }
+        }
+
+        events.sort_by_key(|event| event.date);
+
+        Calendar { events }
+    }
+
+    /// Write the events as a table, one row per event
+    pub fn print(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let name_width = self
+            .events
+            .iter()
+            .map(|event| event.grant_name.len())
+            .chain(std::iter::once("Grant".len()))
+            .max()
+            .unwrap_or_default();
+
+        writeln!(
+            out,
+            "{:<10}  {:<name_width$}  {:<6}  {:>9}  {:>10}  {:>14}",
+            "Date", "Grant", "Type", "Shares", "Unit Value", "Value"
+        )?;
+
+        for event in &self.events {
+            writeln!(
+                out,
+                "{:<10}  {:<name_width$}  {:<6}  {:>9}  {:>10}  {:>14}",
+                event.date,
+                event.grant_name,
+                event.kind,
+                event.shares,
+                format_currency(event.unit_value),
+                format_currency(event.value),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the events as an iCalendar file, with a display alarm the given
+    /// number of days before each vest. Fails on events iCalendar cannot
+    /// represent, before year 0 or after 9999-12-30.
+    pub fn print_ics(&self, out: &mut impl Write, reminder_days: &[u32]) -> std::io::Result<()> {
+        let stamp = Utc::now().format("%Y%m%dT%H%M%SZ");
+
+        let mut lines: Vec<String> = vec![
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            "PRODID:-//equitycalc//Vesting Calendar//EN".to_string(),
+            "CALSCALE:GREGORIAN".to_string(),
+        ];
+
+        for event in &self.events {
+            // iCalendar dates have four digit years
+            let end = event
+                .date
+                .succ_opt()
+                .filter(|end| event.date.year() >= 0 && end.year() <= 9999)
+                .ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        format!("vesting on {} cannot be written to iCalendar", event.date),
+                    )
+                })?;
+            let summary = format!(
+                "{} shares vest ({} {})",
+                event.shares, event.grant_name, event.kind
+            );
+            let description = format!(
+                "{} {} shares of {} vest, worth an estimated {} ({} per share).",
+                event.shares,
+                event.kind,
+                event.grant_name,
+                format_currency(event.value),
+                format_currency(event.unit_value),
+            );
+
+            lines.push("BEGIN:VEVENT".to_string());
+            lines.push(format!(
+                "UID:{}-{}-{}-{}@equitycalc",
+                event.date.format("%Y%m%d"),
+                event.kind.to_lowercase(),
+                event.grant_index,
+                slugify(&event.grant_name)
+            ));
+            lines.push(format!("DTSTAMP:{}", stamp));
+            lines.push(format!(
+                "DTSTART;VALUE=DATE:{}",
+                event.date.format("%Y%m%d")
+            ));
+            lines.push(format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")));
+            lines.push(format!("SUMMARY:{}", escape_ics_text(&summary)));
+            lines.push(format!("DESCRIPTION:{}", escape_ics_text(&description)));
+            lines.push("TRANSP:TRANSPARENT".to_string());
+
+            for days in reminder_days {
+                lines.push("BEGIN:VALARM".to_string());
+                lines.push("ACTION:DISPLAY".to_string());
+                lines.push(format!("TRIGGER:-P{}D", days));
+                lines.push(format!("DESCRIPTION:{}", escape_ics_text(&summary)));
+                lines.push("END:VALARM".to_string());
+            }
+
+            lines.push("END:VEVENT".to_string());
+        }
+
+        lines.push("END:VCALENDAR".to_string());
+
+        for line in lines {
+            out.write_all(fold_ics_line(&line).as_bytes())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::{
+        psp::PreferredStockPriceValuation,
+        rsu::{
+            RestrictedStockUnitValue, RestrictedStockUnitVestingEvent,
+            RestrictedStockUnitVestingSchedule,
+        },
+    };
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn rsu_grant(name: &str, events: Vec<(NaiveDate, i32)>) -> RestrictedStockUnitGrant {
+        RestrictedStockUnitGrant::new(
+            name.to_string(),
+            date(2024, 1, 1),
+            RestrictedStockUnitValue::new(100, 1000),
+            RestrictedStockUnitVestingSchedule::new(
+                date(2024, 1, 1),
+                events
+                    .into_iter()
+                    .map(|(date, number)| RestrictedStockUnitVestingEvent::new(date, number))
+                    .collect(),
+            ),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_new() {
+        let psp = PreferredStockPrice::new(vec![PreferredStockPriceValuation::new(
+            date(2024, 1, 1),
+            250,
+        )]);
+        let rsu_grants = vec![
+            rsu_grant("B", vec![(date(2024, 3, 1), 10), (date(2024, 9, 1), 10)]),
+            rsu_grant("A", vec![(date(2024, 6, 1), 4)]),
+        ];
+
+        let calendar = Calendar::new(&psp, &[], &rsu_grants, date(2024, 3, 2));
+
+        // Events before `from` are left out and the rest are in date order
+        let events: Vec<_> = calendar
+            .events
+            .iter()
+            .map(|event| (event.date, event.grant_name.as_str(), event.grant_index))
+            .collect();
+        assert_eq!(
+            vec![(date(2024, 6, 1), "A", 1), (date(2024, 9, 1), "B", 0)],
+            events
+        );
+        assert_eq!(250, calendar.events[0].unit_value);
+        assert_eq!(1000, calendar.events[0].value);
+    }
+
+    #[test]
+    fn test_print_ics() {
+        let rsu_grants = vec![
+            rsu_grant("Grant A", vec![(date(2024, 6, 1), 4)]),
+            rsu_grant("grant-a", vec![(date(2024, 6, 1), 6)]),
+        ];
+        let calendar = Calendar::new(
+            &PreferredStockPrice::new(vec![]),
+            &[],
+            &rsu_grants,
+            date(2024, 1, 1),
+        );
+
+        let mut out = Vec::new();
+        calendar.print_ics(&mut out, &[7]).unwrap();
+        let ics = String::from_utf8(out).unwrap();
+
+        // DTSTAMP is the time the file is written
+        let lines: Vec<&str> = ics
+            .split("\r\n")
+            .filter(|line| !line.starts_with("DTSTAMP:"))
+            .collect();
+        assert_eq!(
+            vec![
+                "BEGIN:VCALENDAR",
+                "VERSION:2.0",
+                "PRODID:-//equitycalc//Vesting Calendar//EN",
+                "CALSCALE:GREGORIAN",
+                "BEGIN:VEVENT",
+                "UID:20240601-rsu-0-grant-a@equitycalc",
+                "DTSTART;VALUE=DATE:20240601",
+                "DTEND;VALUE=DATE:20240602",
+                "SUMMARY:4 shares vest (Grant A RSU)",
+                "DESCRIPTION:4 RSU shares of Grant A vest\\, worth an estimated 0.00 (0.00 pe",
+                " r share).",
+                "TRANSP:TRANSPARENT",
+                "BEGIN:VALARM",
+                "ACTION:DISPLAY",
+                "TRIGGER:-P7D",
+                "DESCRIPTION:4 shares vest (Grant A RSU)",
+                "END:VALARM",
+                "END:VEVENT",
+                "BEGIN:VEVENT",
+                "UID:20240601-rsu-1-grant-a@equitycalc",
+                "DTSTART;VALUE=DATE:20240601",
+                "DTEND;VALUE=DATE:20240602",
+                "SUMMARY:6 shares vest (grant-a RSU)",
+                "DESCRIPTION:6 RSU shares of grant-a vest\\, worth an estimated 0.00 (0.00 pe",
+                " r share).",
+                "TRANSP:TRANSPARENT",
+                "BEGIN:VALARM",
+                "ACTION:DISPLAY",
+                "TRIGGER:-P7D",
+                "DESCRIPTION:6 shares vest (grant-a RSU)",
+                "END:VALARM",
+                "END:VEVENT",
+                "END:VCALENDAR",
+                "",
+            ],
+            lines
+        );
+
+        let last = vec![rsu_grant("Last", vec![(date(9999, 12, 31), 1)])];
+        let calendar = Calendar::new(
+            &PreferredStockPrice::new(vec![]),
+            &[],
+            &last,
+            date(2024, 1, 1),
+        );
+        assert!(calendar.print_ics(&mut Vec::new(), &[]).is_err());
+    }
+
+    #[test]
+    fn test_escape_ics_text() {
+        assert_eq!("a\\, b\\; c\\\\d\\ne", escape_ics_text("a, b; c\\d\ne"));
+    }
+
+    #[test]
+    fn test_fold_ics_line() {
+        assert_eq!("short\r\n", fold_ics_line("short"));
+
+        let long = "x".repeat(80);
+        assert_eq!(
+            format!("{}\r\n {}\r\n", "x".repeat(75), "x".repeat(5)),
+            fold_ics_line(&long)
+        );
+    }
+}
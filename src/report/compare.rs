@@ -0,0 +1,200 @@
+use std::io::Write;
+
+use chrono::{Months, NaiveDate};
+
+use crate::{
+    model::{option::OptionGrant, psp::PreferredStockPrice, rsu::RestrictedStockUnitGrant},
+    report::{
+        format_currency,
+        incr::{ColumnGroups, PeriodType, Report, ReportOptions},
+    },
+};
+
+/// A portfolio to compare, valued with its own preferred stock price
+pub struct ComparedPortfolio<'a> {
+    /// Name of the portfolio, as shown in the comparison
+    pub name: String,
+
+    /// The price the portfolio's grants are valued at
+    pub psp: &'a PreferredStockPrice,
+
+    /// The portfolio's option grants
+    pub option_grants: &'a Vec<OptionGrant>,
+
+    /// The portfolio's RSU grants
+    pub rsu_grants: &'a Vec<RestrictedStockUnitGrant>,
+}
+
+/// Value vesting each year in several portfolios
+pub struct Comparison {
+    /// Name of each portfolio, in the order given
+    pub names: Vec<String>,
+
+    /// Value vesting in each year, in cents, per portfolio
+    pub by_portfolio: Vec<Vec<i64>>,
+}
+
+impl Comparison {
+    /// Compare the value vesting in each of the first `horizon_years` years of
+    /// each portfolio, counted from when its vesting commences, or say why the
+    /// years cannot be represented
+    pub fn new(portfolios: &[ComparedPortfolio], horizon_years: u32) -> Result<Comparison, String> {
+        let mut names = Vec::new();
+        let mut by_portfolio = Vec::new();
+
+        for portfolio in portfolios {
+            let start = portfolio
+                .rsu_grants
+                .iter()
+                .map(|grant| grant.vesting_schedule.commences_on)
+                .chain(
+                    portfolio
+                        .option_grants
+                        .iter()
+                        .map(|grant| grant.vesting_schedule.commences_on),
+                )
+                .min();
+
+            // A portfolio without grants vests nothing
+            let Some(start) = start else {
+                names.push(portfolio.name.clone());
+                by_portfolio.push(vec![0; horizon_years as usize]);
+                continue;
+            };
+
+            let boundaries = (0..=horizon_years)
+                .map(|year| {
+                    year.checked_mul(12)
+                        .and_then(|months| start.checked_add_months(Months::new(months)))
+                        .ok_or_else(|| {
+                            format!("{} years after {} cannot be represented", year, start)
+                        })
+                })
+                .collect::<Result<Vec<NaiveDate>, String>>()?;
+
+            let report = Report::new(
+                portfolio.psp,
+                portfolio.option_grants,
+                portfolio.rsu_grants,
+                ReportOptions {
+                    period_type: PeriodType::Custom(boundaries),
+                    columns: ColumnGroups::default(),
+                },
+            )?;
+
+            names.push(portfolio.name.clone());
+            by_portfolio.push(report.totals());
+        }
+
+        Ok(Comparison {
+            names,
+            by_portfolio,
+        })
+    }
+
+    /// Total value vesting over the horizon, per portfolio
+    pub fn horizon_totals(&self) -> Vec<(String, i64)> {
+        self.names
+            .iter()
+            .cloned()
+            .zip(self.by_portfolio.iter().map(|years| years.iter().sum()))
+            .collect()
+    }
+
+    /// Write the comparison as CSV, one row per year
+    pub fn print(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let mut header = vec!["Year".to_string()];
+        for name in &self.names {
+            header.push(format!("{} Vested", name));
+            header.push(format!("{} Cumulative", name));
+        }
+        writeln!(out, "{}", header.join(","))?;
+
+        let years = self.by_portfolio.first().map_or(0, |years| years.len());
+        let mut cumulative = vec![0; self.by_portfolio.len()];
+        for year in 0..years {
+            let mut row = vec![(year + 1).to_string()];
+            for (i, years) in self.by_portfolio.iter().enumerate() {
+                cumulative[i] += years[year];
+                row.push(format_currency(years[year]));
+                row.push(format_currency(cumulative[i]));
+            }
+            writeln!(out, "{}", row.join(","))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::{
+        psp::PreferredStockPriceValuation,
+        rsu::{
+            RestrictedStockUnitValue, RestrictedStockUnitVestingEvent,
+            RestrictedStockUnitVestingSchedule,
+        },
+    };
+
+    #[test]
+    fn test_compare() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let psp = PreferredStockPrice::new(vec![
+            PreferredStockPriceValuation::new(date(2020, 1, 1), 100),
+            PreferredStockPriceValuation::new(date(2021, 1, 1), 200),
+        ]);
+        let rsu_grants = vec![RestrictedStockUnitGrant::new(
+            "Grant".to_string(),
+            date(2020, 1, 1),
+            RestrictedStockUnitValue::new(100, 20000),
+            RestrictedStockUnitVestingSchedule::new(
+                date(2020, 1, 1),
+                vec![
+                    RestrictedStockUnitVestingEvent::new(date(2020, 7, 1), 100),
+                    RestrictedStockUnitVestingEvent::new(date(2021, 7, 1), 100),
+                    RestrictedStockUnitVestingEvent::new(date(2022, 7, 1), 100),
+                ],
+            ),
+            None,
+        )];
+        let none = vec![];
+
+        let comparison = Comparison::new(
+            &[
+                ComparedPortfolio {
+                    name: "offer".to_string(),
+                    psp: &psp,
+                    option_grants: &none,
+                    rsu_grants: &rsu_grants,
+                },
+                ComparedPortfolio {
+                    name: "empty".to_string(),
+                    psp: &psp,
+                    option_grants: &none,
+                    rsu_grants: &vec![],
+                },
+            ],
+            2,
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![vec![10000, 20000], vec![0, 0]],
+            comparison.by_portfolio
+        );
+        assert_eq!(
+            vec![("offer".to_string(), 30000), ("empty".to_string(), 0)],
+            comparison.horizon_totals()
+        );
+
+        let mut out = Vec::new();
+        comparison.print(&mut out).unwrap();
+        assert_eq!(
+            "Year,offer Vested,offer Cumulative,empty Vested,empty Cumulative\n\
+             1,100.00,100.00,0.00,0.00\n\
+             2,200.00,300.00,0.00,0.00\n",
+            String::from_utf8(out).unwrap()
+        );
+    }
+}
@@ -0,0 +1,299 @@
+use std::io::Write;
+
+use chrono::NaiveDate;
+
+use crate::{
+    model::{
+        cap_table::CapTable, option::OptionGrant, psp::PreferredStockPrice,
+        rsu::RestrictedStockUnitGrant,
+    },
+    report::format_currency,
+};
+
+/// Ownership today or after a funding round
+pub struct DilutionLine {
+    /// "Current", or the name of the round
+    pub name: String,
+
+    /// The day of the round, or of the report for the current line
+    pub date: NaiveDate,
+
+    /// Price per share, in cents, or `None` before the first valuation
+    pub price: Option<i32>,
+
+    /// Shares the round issues
+    pub new_shares: i64,
+
+    /// Fully diluted shares after the round
+    pub fully_diluted_shares: i64,
+
+    /// Shares granted across all grants
+    pub our_shares: i64,
+
+    /// Value of the granted shares, in cents
+    pub our_value: i64,
+}
+
+/// Ownership of the fully diluted shares over planned funding rounds
+pub struct DilutionReport {
+    /// The current ownership, then each round in date order
+    pub lines: Vec<DilutionLine>,
+}
+
+/// Shares granted on or before `date`, less any forfeited by leaving on or
+/// before it, and their value at `price`, or no value without a price
+fn holdings(
+    option_grants: &[OptionGrant],
+    rsu_grants: &[RestrictedStockUnitGrant],
+    date: &NaiveDate,
+    price: Option<i32>,
+) -> (i64, i64) {
+    let price = price.unwrap_or(0);
+
+    let mut shares = 0;
+    let mut value = 0;
+
+    for grant in option_grants.iter().filter(|g| &g.granted_on <= date) {
+        // Unvested options are forfeited when leaving, and a terminated
+        // grant's schedule ends when it does
+        let number = if grant.terminated_on.is_some_and(|left_on| &left_on <= date) {
+            grant
+                .vesting_schedule
+                .events
+                .iter()
+                .map(|event| event.number)
+                .sum()
+        } else {
+            grant.value.number
+        };
+        shares += number as i64;
+        value += number as i64 * (price - grant.value.exercise_price).max(0) as i64;
+    }
+
+    for grant in rsu_grants.iter().filter(|g| &g.granted_on <= date) {
+        // RSUs are the units in the vesting schedule, as in the summary
+        let units = grant.actual_total_units();
+        shares += units as i64;
+        value += units as i64 * price as i64;
+    }
+
+    (shares, value)
+}
+
+impl DilutionReport {
+    /// Ownership of the fully diluted shares as of `as_of`, counting the
+    /// rounds on or before it, and after each later round
+    pub fn new(
+        cap_table: &CapTable,
+        psp: &PreferredStockPrice,
+        option_grants: &[OptionGrant],
+        rsu_grants: &[RestrictedStockUnitGrant],
+        as_of: NaiveDate,
+    ) -> DilutionReport {
+        let mut fully_diluted_shares = cap_table.as_of(&as_of).fully_diluted_shares();
+
+        let price = psp.value_on(&as_of);
+        let (our_shares, our_value) = holdings(option_grants, rsu_grants, &as_of, price);
+        let mut lines = vec![DilutionLine {
+            name: "Current".to_string(),
+            date: as_of,
+            price,
+            new_shares: 0,
+            fully_diluted_shares,
+            our_shares,
+            our_value,
+        }];
+
+        for round in cap_table.rounds.iter().filter(|round| round.date > as_of) {
+            fully_diluted_shares += round.new_shares;
+            let (our_shares, our_value) = holdings(
+                option_grants,
+                rsu_grants,
+                &round.date,
+                Some(round.price_cents),
+            );
+            lines.push(DilutionLine {
+                name: round.name.clone(),
+                date: round.date,
+                price: Some(round.price_cents),
+                new_shares: round.new_shares,
+                fully_diluted_shares,
+                our_shares,
+                our_value,
+            });
+        }
+
+        DilutionReport { lines }
+    }
+
+    /// Write the ownership as a table, one row per line
+    pub fn print(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let name_width = self
+            .lines
+            .iter()
+            .map(|line| line.name.len())
+            .chain(std::iter::once("Round".len()))
+            .max()
+            .unwrap_or_default();
+
+        writeln!(
+            out,
+            "{:<name_width$}  {:<10}  {:>10}  {:>12}  {:>14}  {:>10}  {:>9}  {:>14}",
+            "Round",
+            "Date",
+            "Price",
+            "New Shares",
+            "Fully Diluted",
+            "Our Shares",
+            "Ownership",
+            "Our Value"
+        )?;
+
+        for line in &self.lines {
+            let ownership = if line.fully_diluted_shares > 0 {
+                line.our_shares as f64 / line.fully_diluted_shares as f64 * 100.0
+            } else {
+                0.0
+            };
+            writeln!(
+                out,
+                "{:<name_width$}  {:<10}  {:>10}  {:>12}  {:>14}  {:>10}  {:>8.4}%  {:>14}",
+                line.name,
+                line.date,
+                line.price.map_or("-".to_string(), format_currency),
+                line.new_shares,
+                line.fully_diluted_shares,
+                line.our_shares,
+                ownership,
+                format_currency(line.our_value),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::{
+        cap_table::FundingRound,
+        option::{
+            ExercisePeriod, OptionGrantValue, OptionGrantVestingEvent, OptionGrantVestingSchedule,
+        },
+        psp::PreferredStockPriceValuation,
+        rsu::{
+            RestrictedStockUnitValue, RestrictedStockUnitVestingEvent,
+            RestrictedStockUnitVestingSchedule,
+        },
+    };
+
+    #[test]
+    fn test_past_rounds_are_current() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let round = |name: &str, date: NaiveDate, new_shares: i64, price_cents: i32| FundingRound {
+            name: name.to_string(),
+            date,
+            new_shares,
+            price_cents,
+        };
+        let cap_table = CapTable::new(
+            800,
+            200,
+            vec![],
+            vec![
+                round("B", date(2025, 1, 1), 500, 400),
+                round("A", date(2024, 1, 1), 1000, 200),
+            ],
+        );
+        let psp = PreferredStockPrice::new(vec![PreferredStockPriceValuation::new(
+            date(2024, 1, 1),
+            200,
+        )]);
+        let rsu_grants = vec![RestrictedStockUnitGrant::new(
+            "Grant".to_string(),
+            date(2023, 1, 1),
+            RestrictedStockUnitValue::new(100, 10000),
+            RestrictedStockUnitVestingSchedule::new(
+                date(2023, 1, 1),
+                vec![RestrictedStockUnitVestingEvent::new(date(2024, 1, 1), 100)],
+            ),
+            None,
+        )];
+
+        let report = DilutionReport::new(&cap_table, &psp, &[], &rsu_grants, date(2024, 6, 1));
+
+        let lines: Vec<(&str, i64, i64, i64)> = report
+            .lines
+            .iter()
+            .map(|line| {
+                (
+                    line.name.as_str(),
+                    line.fully_diluted_shares,
+                    line.our_shares,
+                    line.our_value,
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![("Current", 2000, 100, 20000), ("B", 2500, 100, 40000)],
+            lines
+        );
+    }
+
+    #[test]
+    fn test_units_in_schedule_after_leaving() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let cap_table = CapTable::new(1000, 0, vec![], vec![]);
+        let psp = PreferredStockPrice::new(vec![PreferredStockPriceValuation::new(
+            date(2023, 1, 1),
+            300,
+        )]);
+        let option_grants = vec![OptionGrant::new(
+            "Options".to_string(),
+            date(2023, 1, 1),
+            OptionGrantValue::new(100, 40),
+            OptionGrantVestingSchedule::new(
+                date(2023, 1, 1),
+                vec![
+                    OptionGrantVestingEvent::new(date(2024, 1, 1), 20),
+                    OptionGrantVestingEvent::new(date(2025, 1, 1), 20),
+                ],
+            ),
+            ExercisePeriod::default(),
+            date(2033, 1, 1),
+            None,
+        )
+        .terminate(date(2024, 6, 1))];
+        // The value implies 100 units, but the schedule has 50
+        let rsu_grants = vec![RestrictedStockUnitGrant::new(
+            "RSUs".to_string(),
+            date(2023, 1, 1),
+            RestrictedStockUnitValue::new(100, 10000),
+            RestrictedStockUnitVestingSchedule::new(
+                date(2023, 1, 1),
+                vec![RestrictedStockUnitVestingEvent::new(date(2024, 1, 1), 50)],
+            ),
+            None,
+        )];
+
+        let before = DilutionReport::new(
+            &cap_table,
+            &psp,
+            &option_grants,
+            &rsu_grants,
+            date(2024, 3, 1),
+        );
+        assert_eq!(90, before.lines[0].our_shares);
+
+        let after = DilutionReport::new(
+            &cap_table,
+            &psp,
+            &option_grants,
+            &rsu_grants,
+            date(2024, 7, 1),
+        );
+        assert_eq!(70, after.lines[0].our_shares);
+        assert_eq!(20 * 200 + 50 * 300, after.lines[0].our_value);
+    }
+}
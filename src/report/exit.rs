@@ -0,0 +1,293 @@
+use std::io::Write;
+
+use chrono::NaiveDate;
+
+use crate::{
+    model::{cap_table::Waterfall, option::OptionGrant, rsu::RestrictedStockUnitGrant},
+    report::{format_currency, total::VestingState},
+};
+
+/// A grant valued at the exit price
+pub struct ExitGrant {
+    /// Name of the grant
+    pub name: String,
+
+    /// "Option" or "RSU"
+    pub kind: &'static str,
+
+    /// Shares or units vested on the exit date
+    pub vested: i32,
+
+    /// Shares or units not vested on the exit date
+    pub unvested: i32,
+
+    /// Value of each share or unit, in cents, net of any exercise price
+    pub unit_value: i32,
+
+    /// Whether the exercise price is above the exit price
+    pub underwater: bool,
+}
+
+impl ExitGrant {
+    /// Value of the vested shares or units, in cents
+    pub fn vested_value(&self) -> i64 {
+        self.vested as i64 * self.unit_value as i64
+    }
+
+    /// Value of the unvested shares or units, in cents
+    pub fn unvested_value(&self) -> i64 {
+        self.unvested as i64 * self.unit_value as i64
+    }
+}
+
+/// Grants valued at the common share price resulting from an exit
+pub struct ExitReport {
+    /// The day of the exit
+    pub exit_on: NaiveDate,
+
+    /// How the exit's proceeds are paid out
+    pub waterfall: Waterfall,
+
+    /// Each grant, options first
+    pub grants: Vec<ExitGrant>,
+}
+
+impl ExitReport {
+    /// Value the grants, as vested on `exit_on`, at the common share price
+    /// resulting from `waterfall`
+    pub fn new(
+        waterfall: Waterfall,
+        option_grants: &Vec<OptionGrant>,
+        rsu_grants: &Vec<RestrictedStockUnitGrant>,
+        exit_on: NaiveDate,
+    ) -> ExitReport {
+        let mut state = VestingState::new(option_grants, rsu_grants);
+        state.advance_to(exit_on);
+
+        let price = waterfall.price_per_common_share_cents;
+
+        let mut grants: Vec<ExitGrant> = Vec::new();
+        for (i, grant) in option_grants.iter().enumerate() {
+            let spread = price - grant.value.exercise_price;
+            grants.push(ExitGrant {
+                name: grant.name.clone(),
+                kind: "Option",
+                vested: state.options_vested_units[i],
+                unvested: state.options_unvested_units[i],
+                unit_value: spread.max(0),
+                underwater: spread < 0,
+            });
+        }
+
+        for (i, grant) in rsu_grants.iter().enumerate() {
+            grants.push(ExitGrant {
+                name: grant.name.clone(),
+                kind: "RSU",
+                vested: state.rsu_vested_units[i],
+                unvested: state.rsu_unvested_units[i],
+                unit_value: price,
+                underwater: false,
+            });
+        }
+
+        ExitReport {
+            exit_on,
+            waterfall,
+            grants,
+        }
+    }
+
+    /// Write the waterfall, then the value of each grant
+    pub fn print(&self, out: &mut impl Write) -> std::io::Result<()> {
+        writeln!(
+            out,
+            "Exit on {} at {}\n",
+            self.exit_on,
+            format_currency(self.waterfall.exit_value_cents)
+        )?;
+
+        let series_width = self
+            .waterfall
+            .series
+            .iter()
+            .map(|series| series.name.len())
+            .chain(std::iter::once("Common".len()))
+            .max()
+            .unwrap_or_default();
+
+        writeln!(
+            out,
+            "{:<series_width$}  {:<10}  {:>18}",
+            "Series", "Takes", "Payout"
+        )?;
+        for series in &self.waterfall.series {
+            writeln!(
+                out,
+                "{:<series_width$}  {:<10}  {:>18}",
+                series.name,
+                if series.converted {
+                    "Common"
+                } else {
+                    "Preference"
+                },
+                format_currency(series.payout_cents),
+            )?;
+        }
+        writeln!(
+            out,
+            "{:<series_width$}  {:<10}  {:>18}",
+            "Common",
+            "",
+            format_currency(self.waterfall.common_payout_cents),
+        )?;
+
+        writeln!(
+            out,
+            "\nPrice per common share: {}\n",
+            format_currency(self.waterfall.price_per_common_share_cents)
+        )?;
+
+        let name_width = self
+            .grants
+            .iter()
+            .map(|grant| grant.name.len())
+            .chain(std::iter::once("Total".len()))
+            .max()
+            .unwrap_or_default();
+
+        writeln!(
+            out,
+            "{:<name_width$}  {:<6}  {:>9}  {:>9}  {:>10}  {:>14}  {:>14}",
+            "Grant", "Type", "Vested", "Unvested", "Unit Value", "Vested Value", "Unvested Value"
+        )?;
+        for grant in &self.grants {
+            writeln!(
+                out,
+                "{:<name_width$}  {:<6}  {:>9}  {:>9}  {:>10}  {:>14}  {:>14}{}",
+                grant.name,
+                grant.kind,
+                grant.vested,
+                grant.unvested,
+                format_currency(grant.unit_value),
+                format_currency(grant.vested_value()),
+                format_currency(grant.unvested_value()),
+                if grant.underwater { "  underwater" } else { "" },
+            )?;
+        }
+        writeln!(
+            out,
+            "{:<name_width$}  {:<6}  {:>9}  {:>9}  {:>10}  {:>14}  {:>14}",
+            "Total",
+            "",
+            self.grants.iter().map(|grant| grant.vested).sum::<i32>(),
+            self.grants.iter().map(|grant| grant.unvested).sum::<i32>(),
+            "",
+            format_currency(self.grants.iter().map(ExitGrant::vested_value).sum::<i64>()),
+            format_currency(
+                self.grants
+                    .iter()
+                    .map(ExitGrant::unvested_value)
+                    .sum::<i64>()
+            ),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::{
+        cap_table::{CapTable, PreferredSeries},
+        option::{
+            ExercisePeriod, OptionGrantValue, OptionGrantVestingEvent, OptionGrantVestingSchedule,
+        },
+        rsu::{
+            RestrictedStockUnitValue, RestrictedStockUnitVestingEvent,
+            RestrictedStockUnitVestingSchedule,
+        },
+    };
+
+    #[test]
+    fn test_waterfall_to_payout() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let cap_table = CapTable::new(
+            1_000,
+            0,
+            vec![PreferredSeries {
+                name: "A".to_string(),
+                shares: 1_000,
+                issue_price_cents: 1_000,
+                liquidation_multiple: 1.0,
+                participating: false,
+                participation_cap: None,
+                seniority: 0,
+            }],
+            vec![],
+        );
+        let option = |name: &str, exercise_price: i32| {
+            OptionGrant::new(
+                name.to_string(),
+                date(2023, 1, 1),
+                OptionGrantValue::new(exercise_price, 100),
+                OptionGrantVestingSchedule::new(
+                    date(2023, 1, 1),
+                    vec![
+                        OptionGrantVestingEvent::new(date(2024, 1, 1), 40),
+                        OptionGrantVestingEvent::new(date(2025, 1, 1), 60),
+                    ],
+                ),
+                ExercisePeriod::default(),
+                date(2033, 1, 1),
+                None,
+            )
+        };
+        let option_grants = vec![option("In", 300), option("Under", 600)];
+        let rsu_grants = vec![RestrictedStockUnitGrant::new(
+            "RSUs".to_string(),
+            date(2023, 1, 1),
+            RestrictedStockUnitValue::new(100, 5_000),
+            RestrictedStockUnitVestingSchedule::new(
+                date(2023, 1, 1),
+                vec![
+                    RestrictedStockUnitVestingEvent::new(date(2024, 1, 1), 20),
+                    RestrictedStockUnitVestingEvent::new(date(2025, 1, 1), 30),
+                ],
+            ),
+            None,
+        )];
+
+        // Series A takes its 1,000,000 preference, leaving 500,000 for 1,000
+        // common shares
+        let report = ExitReport::new(
+            cap_table.waterfall(1_500_000),
+            &option_grants,
+            &rsu_grants,
+            date(2024, 6, 1),
+        );
+        assert_eq!(500, report.waterfall.price_per_common_share_cents);
+
+        let grants: Vec<(&str, i32, i32, i32, bool)> = report
+            .grants
+            .iter()
+            .map(|grant| {
+                (
+                    grant.name.as_str(),
+                    grant.vested,
+                    grant.unvested,
+                    grant.unit_value,
+                    grant.underwater,
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("In", 40, 60, 200, false),
+                ("Under", 40, 60, 0, true),
+                ("RSUs", 20, 30, 500, false),
+            ],
+            grants
+        );
+        assert_eq!(8_000, report.grants[0].vested_value());
+        assert_eq!(15_000, report.grants[2].unvested_value());
+    }
+}
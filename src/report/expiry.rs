@@ -0,0 +1,184 @@
+use std::io::Write;
+
+use chrono::{Months, NaiveDate};
+
+use crate::{
+    model::{option::OptionGrant, psp::PreferredStockPrice},
+    report::format_currency,
+};
+
+/// An option grant with vested options that expire soon
+pub struct ExpiringGrant {
+    /// Name of the grant
+    pub name: String,
+
+    /// Last day the vested options can be exercised
+    pub expires_on: NaiveDate,
+
+    /// Options vested by the start of the window
+    pub vested: i32,
+
+    /// Value of the vested options, in cents, net of the exercise price
+    pub value: i64,
+}
+
+/// Option grants that can last be exercised within a window
+pub struct ExpiryReport {
+    /// First day of the window
+    pub as_of: NaiveDate,
+
+    /// Last day of the window
+    pub until: NaiveDate,
+
+    /// The grants expiring in the window
+    pub grants: Vec<ExpiringGrant>,
+}
+
+impl ExpiryReport {
+    /// Option grants with vested options that can last be exercised between
+    /// `as_of` and `within_months` months later
+    pub fn new(
+        psp: &PreferredStockPrice,
+        option_grants: &[OptionGrant],
+        as_of: NaiveDate,
+        within_months: u32,
+    ) -> ExpiryReport {
+        // A window past the last date that can be represented includes every
+        // later expiry
+        let until = as_of
+            .checked_add_months(Months::new(within_months))
+            .unwrap_or(NaiveDate::MAX);
+
+        let mut grants: Vec<ExpiringGrant> = option_grants
+            .iter()
+            .filter(|grant| {
+                let expires_on = grant.exercisable_until();
+                expires_on >= as_of && expires_on <= until
+            })
+            .map(|grant| {
+                let vested: i32 = grant
+                    .vesting_schedule
+                    .events
+                    .iter()
+                    .filter(|event| event.date <= as_of)
+                    .map(|event| event.number)
+                    .sum();
+                ExpiringGrant {
+                    name: grant.name.clone(),
+                    expires_on: grant.exercisable_until(),
+                    vested,
+                    value: vested as i64
+                        * psp
+                            .vest_price(&as_of, grant.vest_price)
+                            .map_or(0, |price| (price - grant.value.exercise_price).max(0))
+                            as i64,
+                }
+            })
+            .filter(|grant| grant.vested > 0)
+            .collect();
+        grants.sort_by_key(|grant| grant.expires_on);
+
+        ExpiryReport {
+            as_of,
+            until,
+            grants,
+        }
+    }
+
+    /// Write each expiring grant, or that none expire
+    pub fn print(&self, out: &mut impl Write) -> std::io::Result<()> {
+        if self.grants.is_empty() {
+            return writeln!(
+                out,
+                "No vested options expire between {} and {}",
+                self.as_of, self.until
+            );
+        }
+
+        writeln!(
+            out,
+            "Warning: vested options expire between {} and {}:",
+            self.as_of, self.until
+        )?;
+        for grant in &self.grants {
+            writeln!(
+                out,
+                "  {}: {} vested options expire after {}, worth {} today",
+                grant.name,
+                grant.vested,
+                grant.expires_on,
+                format_currency(grant.value)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::{
+        option::{
+            ExercisePeriod, OptionGrantValue, OptionGrantVestingEvent, OptionGrantVestingSchedule,
+        },
+        psp::PreferredStockPriceValuation,
+    };
+
+    #[test]
+    fn test_expiry_window() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let psp = PreferredStockPrice::new(vec![PreferredStockPriceValuation::new(
+            date(2023, 1, 1),
+            500,
+        )]);
+        let option = |name: &str, vests_on: NaiveDate, expires_on: NaiveDate| {
+            OptionGrant::new(
+                name.to_string(),
+                date(2020, 1, 1),
+                OptionGrantValue::new(100, 200),
+                OptionGrantVestingSchedule::new(
+                    date(2020, 1, 1),
+                    vec![
+                        OptionGrantVestingEvent::new(vests_on, 100),
+                        OptionGrantVestingEvent::new(date(2024, 2, 1), 100),
+                    ],
+                ),
+                ExercisePeriod::default(),
+                expires_on,
+                None,
+            )
+        };
+        let option_grants = vec![
+            option("Later", date(2021, 1, 1), date(2024, 5, 1)),
+            option("Soon", date(2021, 1, 1), date(2024, 3, 1)),
+            option("Expired", date(2021, 1, 1), date(2023, 12, 31)),
+            option("Outside", date(2021, 1, 1), date(2024, 7, 2)),
+            option("Unvested", date(2024, 3, 1), date(2024, 4, 1)),
+        ];
+
+        let report = ExpiryReport::new(&psp, &option_grants, date(2024, 1, 1), 6);
+        assert_eq!(date(2024, 7, 1), report.until);
+
+        // Only options vested by the start of the window are counted
+        let grants: Vec<(&str, NaiveDate, i32, i64)> = report
+            .grants
+            .iter()
+            .map(|grant| {
+                (
+                    grant.name.as_str(),
+                    grant.expires_on,
+                    grant.vested,
+                    grant.value,
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("Soon", date(2024, 3, 1), 100, 40_000),
+                ("Later", date(2024, 5, 1), 100, 40_000),
+            ],
+            grants
+        );
+    }
+}
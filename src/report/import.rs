@@ -0,0 +1,147 @@
+use std::io::Write;
+
+use chrono::NaiveDate;
+
+use crate::{
+    model::{option::OptionGrant, rsu::RestrictedStockUnitGrant},
+    report::{event_changes, format_currency, print_event_changes},
+};
+
+/// What a grant was granted: the number of shares or units and their price
+pub type Granted = (i32, i32);
+
+/// A grant that the import adds or changes
+pub struct ImportedGrant {
+    /// Name of the grant
+    pub name: String,
+
+    /// "Option" or "RSU"
+    pub kind: &'static str,
+
+    /// What was granted before and after the import, or None for a new grant
+    pub granted: Option<(Granted, Granted)>,
+
+    /// Date and number of each vesting event the import removes
+    pub removed: Vec<(NaiveDate, i32)>,
+
+    /// Date and number of each vesting event the import adds
+    pub added: Vec<(NaiveDate, i32)>,
+}
+
+/// Differences between a portfolio's grants and the grants imported into it
+pub struct ImportReport {
+    /// The grants the import adds or changes
+    pub grants: Vec<ImportedGrant>,
+}
+
+fn difference(
+    name: &str,
+    kind: &'static str,
+    current: Option<(Granted, Vec<(NaiveDate, i32)>)>,
+    (granted, imported): (Granted, Vec<(NaiveDate, i32)>),
+) -> Option<ImportedGrant> {
+    let Some((current_granted, current)) = current else {
+        return Some(ImportedGrant {
+            name: name.to_string(),
+            kind,
+            granted: None,
+            removed: vec![],
+            added: imported,
+        });
+    };
+
+    let (removed, added) = event_changes(&current, &imported);
+
+    if removed.is_empty() && added.is_empty() && current_granted == granted {
+        None
+    } else {
+        Some(ImportedGrant {
+            name: name.to_string(),
+            kind,
+            granted: Some((current_granted, granted)),
+            removed,
+            added,
+        })
+    }
+}
+
+impl ImportReport {
+    /// Differences between each imported grant and the current grant with the
+    /// same name, if there is one
+    pub fn new(
+        current_option_grants: &[OptionGrant],
+        imported_option_grants: &[OptionGrant],
+        current_rsu_grants: &[RestrictedStockUnitGrant],
+        imported_rsu_grants: &[RestrictedStockUnitGrant],
+    ) -> ImportReport {
+        let option = |grant: &OptionGrant| -> (Granted, Vec<(NaiveDate, i32)>) {
+            let events = grant
+                .vesting_schedule
+                .events
+                .iter()
+                .map(|event| (event.date, event.number))
+                .collect();
+            ((grant.value.number, grant.value.exercise_price), events)
+        };
+        let rsu = |grant: &RestrictedStockUnitGrant| -> (Granted, Vec<(NaiveDate, i32)>) {
+            let events = grant
+                .vesting_schedule
+                .events
+                .iter()
+                .map(|event| (event.date, event.number))
+                .collect();
+            ((grant.value.units(), grant.value.grant_price_cents), events)
+        };
+
+        let options = imported_option_grants.iter().filter_map(|imported| {
+            let current = current_option_grants
+                .iter()
+                .find(|grant| grant.name == imported.name);
+            difference(
+                &imported.name,
+                "Option",
+                current.map(option),
+                option(imported),
+            )
+        });
+        let rsus = imported_rsu_grants.iter().filter_map(|imported| {
+            let current = current_rsu_grants
+                .iter()
+                .find(|grant| grant.name == imported.name);
+            difference(&imported.name, "RSU", current.map(rsu), rsu(imported))
+        });
+
+        ImportReport {
+            grants: options.chain(rsus).collect(),
+        }
+    }
+
+    /// Write each grant the import adds or changes, or that none change
+    pub fn print(&self, out: &mut impl Write) -> std::io::Result<()> {
+        if self.grants.is_empty() {
+            return writeln!(out, "No grants changed");
+        }
+
+        for grant in &self.grants {
+            match grant.granted {
+                None => writeln!(out, "{} ({}): new grant", grant.name, grant.kind)?,
+                Some((current, imported)) if current != imported => writeln!(
+                    out,
+                    "{} ({}): {} at {} -> {} at {}",
+                    grant.name,
+                    grant.kind,
+                    current.0,
+                    format_currency(current.1),
+                    imported.0,
+                    format_currency(imported.1)
+                )?,
+                Some(_) => writeln!(out, "{} ({})", grant.name, grant.kind)?,
+            }
+
+            print_event_changes(out, &grant.removed, &grant.added)?;
+            writeln!(out)?;
+        }
+
+        Ok(())
+    }
+}
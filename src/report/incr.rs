@@ -1,104 +1,260 @@
-use std::{fs::File, io::Write, path::PathBuf};
+use std::io::Write;
 
-use chrono::{Datelike, Days, Months, NaiveDate};
+use chrono::{Datelike, NaiveDate};
 
 use crate::{
     model::{option::OptionGrant, psp::PreferredStockPrice, rsu::RestrictedStockUnitGrant},
     report::format_currency,
 };
 
+/// What vests in a period, with values in cents
 #[derive(Debug)]
 pub struct ReportLine {
-    from: NaiveDate,
-    to: NaiveDate,
-    total: i32,
-    by_grant: Vec<i32>,
+    /// First day of the period
+    pub from: NaiveDate,
+
+    /// Last day of the period
+    pub to: NaiveDate,
+
+    /// Value vesting across all grants
+    pub total: i64,
+
+    /// Value vesting per grant, in the order of `Report::grant_names`
+    pub by_grant: Vec<i64>,
+
+    /// Shares or units vesting across all grants
+    pub shares_total: i32,
+
+    /// Shares or units vesting per grant, in the order of `Report::grant_names`
+    pub shares_by_grant: Vec<i32>,
+
+    /// Shares or units vested through the end of the period
+    pub cumulative_shares: i32,
+
+    /// Value vested through the end of the period
+    pub cumulative_total: i64,
+
+    /// Preferred stock price at the end of the period, if there is one yet
+    pub price: Option<i32>,
 }
 
+/// What vests in each period
 pub struct Report {
-    grant_names: Vec<String>,
-    lines: Vec<ReportLine>,
+    /// Name of each grant, options first
+    pub grant_names: Vec<String>,
+
+    /// The periods, in date order
+    pub lines: Vec<ReportLine>,
+
+    /// Which optional columns to write
+    pub columns: ColumnGroups,
+
+    /// How the report is divided into periods
+    pub period_type: PeriodType,
+
+    /// Shares or units vesting before the first or after the last period,
+    /// which custom periods can leave out
+    pub unreported_shares: i32,
 }
 
-// [1/1, 3/16] -> ($year - 1)-12-17
-// [3/17, 6/16] -> $year-3-17
-// [6/17, 9/16] -> $year-6-17
-// [9/17, 12/16] -> $year-9-17
-// [12/17, 12/31] -> $year-12-17
-fn start_of_skewed_quarter(date: &NaiveDate) -> NaiveDate {
-    if date.month() == 1 || date.month() == 2 || (date.month() == 3 && date.day() <= 16) {
-        NaiveDate::from_ymd_opt(date.year() - 1, 12, 17).unwrap()
-    } else if date.month() == 3
-        || date.month() == 4
-        || date.month() == 5
-        || (date.month() == 6 && date.day() <= 16)
-    {
-        NaiveDate::from_ymd_opt(date.year(), 3, 17).unwrap()
-    } else if date.month() == 6
-        || date.month() == 7
-        || date.month() == 8
-        || (date.month() == 9 && date.day() <= 16)
-    {
-        NaiveDate::from_ymd_opt(date.year(), 6, 17).unwrap()
-    } else if date.month() == 9
-        || date.month() == 10
-        || date.month() == 11
-        || (date.month() == 12 && date.day() <= 16)
-    {
-        NaiveDate::from_ymd_opt(date.year(), 9, 17).unwrap()
-    } else {
-        NaiveDate::from_ymd_opt(date.year(), 12, 17).unwrap()
+/// Day `start_day` of the month `index` months after the start of year 0, or
+/// the last day of that month if it is shorter. `None` if the month cannot be
+/// represented.
+fn start_of_month_period(index: i32, start_day: u32) -> Option<NaiveDate> {
+    let year = index.div_euclid(12);
+    let month = index.rem_euclid(12) as u32 + 1;
+    (1..=start_day)
+        .rev()
+        .find_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+}
+
+/// Start of the recurring period of `months` months containing `date`, where
+/// one of the periods starts on `start_month`/`start_day` of each year, or
+/// `None` if it is before the first date that can be represented.
+///
+/// For example, quarters starting on 12/17 are
+/// [12/17, 3/16], [3/17, 6/16], [6/17, 9/16] and [9/17, 12/16]. Periods
+/// starting on a day some months do not have start on the last day of those
+/// months instead.
+fn start_of_recurring_period(
+    date: &NaiveDate,
+    months: u32,
+    start_month: u32,
+    start_day: u32,
+) -> Option<NaiveDate> {
+    // Count months since year 0, treating days before the month's period start
+    // as part of the previous month
+    let mut index = date.year() * 12 + date.month0() as i32;
+    if *date < start_of_month_period(index, start_day)? {
+        index -= 1;
     }
+
+    let offset = (index - (start_month as i32 - 1)).rem_euclid(months as i32);
+    start_of_month_period(index - offset, start_day)
 }
 
-// [1/1, 3/31] -> $year-1-1
-// [4/1, 6/30] -> $year-4-1
-// [7/1, 9/30] -> $year-7-1
-// [10/1, 12/31] -> $year-10-1
-fn start_of_quarter(date: &NaiveDate) -> NaiveDate {
-    // map month to quarter
-    // 1 -> 1
-    // 3 -> 1
-    // 4 -> 2
-    // etc
-    let quarter = (date.month() - 1) / 3 + 1;
-
-    // map quarter to first month
-    // 1 -> 1
-    // 2 -> 4
-    // 3 -> 7
-    let first_month = (quarter * 3) - 2;
-
-    NaiveDate::from_ymd_opt(date.year(), first_month, 1).unwrap()
+/// How the report is divided into periods
+#[derive(Debug, Clone)]
+pub enum PeriodType {
+    /// Periods of `months` months, one of which starts on `start_month`/`start_day`
+    /// each year. In months without `start_day`, periods start on the last day
+    /// of the month instead. Build with [`PeriodType::recurring`] to check the
+    /// values.
+    Recurring {
+        /// Length of each period
+        months: u32,
+
+        /// Month one of the periods starts in
+        start_month: u32,
+
+        /// Day of the month the periods start on
+        start_day: u32,
+    },
+
+    /// Periods between consecutive boundary dates
+    Custom(Vec<NaiveDate>),
 }
 
-#[derive(Debug, Default, Clone)]
-pub enum QuarterType {
-    #[default]
-    Calendar,
-    Skewed,
+impl Default for PeriodType {
+    fn default() -> Self {
+        PeriodType::calendar_quarter()
+    }
 }
 
-impl QuarterType {
-    pub fn start_of_quarter(&self, date: &NaiveDate) -> NaiveDate {
+impl PeriodType {
+    /// Periods of `months` months, one of which starts on
+    /// `start_month`/`start_day` each year
+    pub fn recurring(months: u32, start_month: u32, start_day: u32) -> Result<PeriodType, String> {
+        if !(1..=12).contains(&months) {
+            return Err(format!("periods must be 1 to 12 months, got {}", months));
+        }
+        if !(1..=12).contains(&start_month) || !(1..=31).contains(&start_day) {
+            return Err(format!(
+                "periods must start on a month from 1 to 12 and a day from 1 to 31, got {}-{}",
+                start_month, start_day
+            ));
+        }
+
+        Ok(PeriodType::Recurring {
+            months,
+            start_month,
+            start_day,
+        })
+    }
+
+    /// Quarters starting on 1/1, 4/1, 7/1 and 10/1
+    pub fn calendar_quarter() -> PeriodType {
+        PeriodType::Recurring {
+            months: 3,
+            start_month: 1,
+            start_day: 1,
+        }
+    }
+
+    /// Quarters of 12/17-3/16, 3/17-6/16, 6/17-9/16, and 9/17-12/16
+    pub fn skewed_quarter() -> PeriodType {
+        PeriodType::Recurring {
+            months: 3,
+            start_month: 12,
+            start_day: 17,
+        }
+    }
+
+    /// Name of a period in report headers: "Quarter" for three month periods,
+    /// "Period" otherwise
+    pub fn name(&self) -> &'static str {
         match self {
-            QuarterType::Calendar => start_of_quarter(date),
-            QuarterType::Skewed => start_of_skewed_quarter(date),
+            PeriodType::Recurring { months: 3, .. } => "Quarter",
+            _ => "Period",
         }
     }
+
+    /// Periods, as inclusive date ranges, that cover `start` through `end`,
+    /// or why they cannot be represented. Custom periods are always reported
+    /// in full.
+    pub fn periods(
+        &self,
+        start: &NaiveDate,
+        end: &NaiveDate,
+    ) -> Result<Vec<(NaiveDate, NaiveDate)>, String> {
+        let out_of_range =
+            |date: &NaiveDate| format!("the period containing {} cannot be represented", date);
+
+        match self {
+            PeriodType::Recurring {
+                months,
+                start_month,
+                start_day,
+            } => {
+                let last = start_of_recurring_period(end, *months, *start_month, *start_day)
+                    .ok_or_else(|| out_of_range(end))?;
+
+                let mut cursor =
+                    start_of_recurring_period(start, *months, *start_month, *start_day)
+                        .ok_or_else(|| out_of_range(start))?;
+                let mut periods = Vec::new();
+                while cursor <= last {
+                    let index = cursor.year() * 12 + cursor.month0() as i32;
+                    let next = start_of_month_period(index + *months as i32, *start_day)
+                        .ok_or_else(|| out_of_range(&cursor))?;
+                    periods.push((
+                        cursor,
+                        next.pred_opt().ok_or_else(|| out_of_range(&cursor))?,
+                    ));
+                    cursor = next;
+                }
+                Ok(periods)
+            }
+            PeriodType::Custom(boundaries) => {
+                let mut boundaries = boundaries.clone();
+                boundaries.sort();
+                boundaries.dedup();
+
+                boundaries
+                    .windows(2)
+                    .map(|pair| {
+                        let end = pair[1].pred_opt().ok_or_else(|| out_of_range(&pair[0]))?;
+                        Ok((pair[0], end))
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Optional groups of columns to include after the per grant values
+#[derive(Debug, Default, Clone)]
+pub struct ColumnGroups {
+    /// Number of shares vesting per grant and in total
+    pub shares: bool,
+
+    /// Shares vested and value vested, from the first period up to and
+    /// including each period
+    pub cumulative: bool,
+
+    /// Preferred stock price at the end of each period, for reference only;
+    /// shares are valued at the price on their vest date
+    pub price: bool,
 }
 
+/// How to divide the report into periods, and which columns to include
 pub struct ReportOptions {
-    pub quarter_type: QuarterType,
+    /// How the report is divided into periods
+    pub period_type: PeriodType,
+
+    /// Which optional columns to write
+    pub columns: ColumnGroups,
 }
 
 impl Report {
+    /// What vests in each period, from when vesting commences through the last
+    /// vesting event, or why the periods cannot be represented
     pub fn new(
         psp: &PreferredStockPrice,
         option_grants: &Vec<OptionGrant>,
         rsu_grants: &Vec<RestrictedStockUnitGrant>,
         options: ReportOptions,
-    ) -> Report {
+    ) -> Result<Report, String> {
         // When vesting starts
         let start_date = rsu_grants
             .iter()
@@ -108,26 +264,26 @@ impl Report {
                     .iter()
                     .map(|option_grant| option_grant.vesting_schedule.commences_on),
             )
-            .min()
-            .unwrap();
-
-        let start_quarter_date = options.quarter_type.start_of_quarter(&start_date);
+            .min();
 
         let end_date = rsu_grants
             .iter()
-            .map(|rsu_grant| rsu_grant.vesting_schedule.events.last().unwrap().date)
+            .map(|rsu_grant| rsu_grant.vesting_ends_on())
             .chain(
                 option_grants
                     .iter()
-                    .map(|option_grant| option_grant.vesting_schedule.events.last().unwrap().date),
+                    .map(|option_grant| option_grant.vesting_ends_on()),
             )
-            .max()
-            .unwrap();
+            .max();
 
-        // Vesting starts quarter start date
-        let end_quarter_date = options.quarter_type.start_of_quarter(&end_date);
+        // Without grants there are no periods to report
+        let periods = match (start_date, end_date) {
+            (Some(start_date), Some(end_date)) => {
+                options.period_type.periods(&start_date, &end_date)?
+            }
+            _ => vec![],
+        };
 
-        let mut cursor = start_quarter_date;
         let mut lines: Vec<ReportLine> = Vec::new();
 
         let grant_names = option_grants
@@ -136,82 +292,142 @@ impl Report {
             .chain(rsu_grants.iter().map(|grant| grant.name.clone()))
             .collect();
 
-        while cursor <= end_quarter_date {
-            let from = cursor;
-            let to = from
-                .checked_add_months(Months::new(3))
-                .unwrap()
-                .checked_sub_days(Days::new(1))
-                .unwrap();
+        let mut cumulative_shares = 0;
+        let mut cumulative_total = 0;
 
+        for (from, to) in periods {
             let mut total = 0;
+            let mut shares_total = 0;
 
-            let mut by_grant: Vec<i32> = Vec::new();
+            let mut by_grant: Vec<i64> = Vec::new();
+            let mut shares_by_grant: Vec<i32> = Vec::new();
             for grant in option_grants {
-                let grant_total = &grant
+                let events = grant
                     .vesting_schedule
                     .events
                     .iter()
-                    .filter(|event| event.date >= from && event.date <= to)
+                    .filter(|event| event.date >= from && event.date <= to);
+                let grant_total: i64 = events
+                    .clone()
                     .map(|event| {
-                        let unit_value = psp.value_on(&event.date) - grant.value.exercise_price;
-                        event.number * unit_value
+                        let unit_value = psp
+                            .vest_price(&event.date, grant.vest_price)
+                            .map_or(0, |price| price - grant.value.exercise_price);
+                        event.number as i64 * unit_value as i64
                     })
                     .sum();
+                let grant_shares: i32 = events.map(|event| event.number).sum();
                 total += grant_total;
-                by_grant.push(*grant_total);
+                shares_total += grant_shares;
+                by_grant.push(grant_total);
+                shares_by_grant.push(grant_shares);
             }
 
             for grant in rsu_grants {
-                let grant_total = &grant
+                let events = grant
                     .vesting_schedule
                     .events
                     .iter()
-                    .filter(|event| event.date >= from && event.date <= to)
+                    .filter(|event| event.date >= from && event.date <= to);
+                let grant_total: i64 = events
+                    .clone()
                     .map(|event| {
-                        let unit_value = psp.value_on(&event.date);
-                        event.number * unit_value
+                        let unit_value = psp.vest_price(&event.date, grant.vest_price).unwrap_or(0);
+                        event.number as i64 * unit_value as i64
                     })
                     .sum();
+                let grant_shares: i32 = events.map(|event| event.number).sum();
                 total += grant_total;
-                by_grant.push(*grant_total);
+                shares_total += grant_shares;
+                by_grant.push(grant_total);
+                shares_by_grant.push(grant_shares);
             }
 
+            cumulative_shares += shares_total;
+            cumulative_total += total;
+
             lines.push(ReportLine {
                 from,
                 to,
                 total,
                 by_grant,
+                shares_total,
+                shares_by_grant,
+                cumulative_shares,
+                cumulative_total,
+                price: psp.value_on(&to),
             });
-
-            cursor = cursor.checked_add_months(Months::new(3)).unwrap();
         }
 
-        Report { grant_names, lines }
+        // Custom periods need not cover every vesting event
+        let vesting_shares: i32 = option_grants
+            .iter()
+            .flat_map(|grant| &grant.vesting_schedule.events)
+            .map(|event| event.number)
+            .chain(
+                rsu_grants
+                    .iter()
+                    .flat_map(|grant| &grant.vesting_schedule.events)
+                    .map(|event| event.number),
+            )
+            .sum();
+
+        Ok(Report {
+            grant_names,
+            lines,
+            columns: options.columns,
+            period_type: options.period_type,
+            unreported_shares: vesting_shares - cumulative_shares,
+        })
     }
 
-    pub fn print_to_file(&self, output: &PathBuf) {
-        let mut file = File::create(output).unwrap();
+    /// Value vesting in each period
+    pub fn totals(&self) -> Vec<i64> {
+        self.lines.iter().map(|line| line.total).collect()
+    }
 
-        file.write_fmt(format_args!(
-            "Quarter Start,Quarter End,{},Total\n",
-            &self.grant_names.join(",")
-        ))
-        .unwrap();
-        for line in &self.lines {
-            file.write_fmt(format_args!(
-                "{},{},{},{}\n",
-                line.from,
-                line.to,
-                line.by_grant
+    /// Write the report as CSV, one row per period
+    pub fn print(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let name = self.period_type.name();
+        let mut header: Vec<String> = vec![format!("{} Start", name), format!("{} End", name)];
+        header.extend(self.grant_names.iter().cloned());
+        header.push("Total".to_string());
+        if self.columns.shares {
+            header.extend(
+                self.grant_names
                     .iter()
-                    .map(|v| format_currency(*v))
-                    .collect::<Vec<String>>()
-                    .join(","),
-                format_currency(line.total),
-            ))
-            .unwrap();
+                    .map(|name| format!("{} Shares", name)),
+            );
+            header.push("Total Shares".to_string());
+        }
+        if self.columns.cumulative {
+            header.push("Cumulative Shares".to_string());
+            header.push("Cumulative Total".to_string());
         }
+        if self.columns.price {
+            header.push(format!("Preferred Stock Price at {} End", name));
+        }
+        writeln!(out, "{}", header.join(","))?;
+
+        for line in &self.lines {
+            let mut row: Vec<String> = vec![line.from.to_string(), line.to.to_string()];
+            row.extend(line.by_grant.iter().map(|v| format_currency(*v)));
+            row.push(format_currency(line.total));
+            if self.columns.shares {
+                row.extend(line.shares_by_grant.iter().map(|v| v.to_string()));
+                row.push(line.shares_total.to_string());
+            }
+            if self.columns.cumulative {
+                row.push(line.cumulative_shares.to_string());
+                row.push(format_currency(line.cumulative_total));
+            }
+            if self.columns.price {
+                row.push(line.price.map(format_currency).unwrap_or_default());
+            }
+            writeln!(out, "{}", row.join(","))?;
+        }
+
+        Ok(())
     }
 }
 
@@ -219,6 +435,14 @@ impl Report {
 mod test {
     use super::*;
 
+    fn start_of_quarter(date: &NaiveDate) -> NaiveDate {
+        start_of_recurring_period(date, 3, 1, 1).unwrap()
+    }
+
+    fn start_of_skewed_quarter(date: &NaiveDate) -> NaiveDate {
+        start_of_recurring_period(date, 3, 12, 17).unwrap()
+    }
+
     #[test]
     fn test_start_of_quarter() {
         assert_eq!(
@@ -241,4 +465,167 @@ mod test {
             start_of_quarter(&NaiveDate::from_ymd_opt(2024, 4, 1).unwrap())
         );
     }
+
+    #[test]
+    fn test_start_of_skewed_quarter() {
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2023, 12, 17).unwrap(),
+            start_of_skewed_quarter(&NaiveDate::from_ymd_opt(2024, 3, 16).unwrap())
+        );
+
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2024, 3, 17).unwrap(),
+            start_of_skewed_quarter(&NaiveDate::from_ymd_opt(2024, 3, 17).unwrap())
+        );
+
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2024, 12, 17).unwrap(),
+            start_of_skewed_quarter(&NaiveDate::from_ymd_opt(2024, 12, 31).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_periods() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        let fiscal_years = PeriodType::Recurring {
+            months: 12,
+            start_month: 2,
+            start_day: 1,
+        };
+        assert_eq!(
+            Ok(vec![
+                (date(2023, 2, 1), date(2024, 1, 31)),
+                (date(2024, 2, 1), date(2025, 1, 31)),
+            ]),
+            fiscal_years.periods(&date(2023, 6, 1), &date(2024, 2, 1))
+        );
+
+        let custom = PeriodType::Custom(vec![date(2024, 7, 1), date(2024, 1, 1), date(2025, 1, 1)]);
+        assert_eq!(
+            Ok(vec![
+                (date(2024, 1, 1), date(2024, 6, 30)),
+                (date(2024, 7, 1), date(2024, 12, 31)),
+            ]),
+            custom.periods(&date(2020, 1, 1), &date(2030, 1, 1))
+        );
+
+        assert!(PeriodType::calendar_quarter()
+            .periods(&date(2024, 1, 1), &NaiveDate::MAX)
+            .is_err());
+    }
+
+    #[test]
+    fn test_price_column() {
+        use crate::model::{
+            psp::PreferredStockPriceValuation,
+            rsu::{
+                RestrictedStockUnitValue, RestrictedStockUnitVestingEvent,
+                RestrictedStockUnitVestingSchedule,
+            },
+        };
+
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let psp = PreferredStockPrice::new(vec![
+            PreferredStockPriceValuation::new(date(2024, 1, 1), 100),
+            PreferredStockPriceValuation::new(date(2024, 3, 1), 200),
+        ]);
+        let rsu_grants = vec![RestrictedStockUnitGrant::new(
+            "RSUs".to_string(),
+            date(2024, 1, 1),
+            RestrictedStockUnitValue::new(100, 1000),
+            RestrictedStockUnitVestingSchedule::new(
+                date(2024, 1, 1),
+                vec![RestrictedStockUnitVestingEvent::new(date(2024, 2, 1), 10)],
+            ),
+            None,
+        )];
+
+        let report = Report::new(
+            &psp,
+            &vec![],
+            &rsu_grants,
+            ReportOptions {
+                period_type: PeriodType::calendar_quarter(),
+                columns: ColumnGroups {
+                    price: true,
+                    ..Default::default()
+                },
+            },
+        )
+        .unwrap();
+
+        // Shares vest at the price on the vest date, not the period end price
+        assert_eq!(1000, report.lines[0].total);
+        assert_eq!(Some(200), report.lines[0].price);
+
+        let mut out = Vec::new();
+        report.print(&mut out).unwrap();
+        assert_eq!(
+            "Quarter Start,Quarter End,RSUs,Total,Preferred Stock Price at Quarter End\n\
+             2024-01-01,2024-03-31,10.00,10.00,2.00\n",
+            String::from_utf8(out).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_recurring() {
+        assert!(PeriodType::recurring(6, 2, 28).is_ok());
+        assert!(PeriodType::recurring(1, 1, 31).is_ok());
+        assert!(PeriodType::recurring(0, 1, 1).is_err());
+        assert!(PeriodType::recurring(3, 13, 1).is_err());
+        assert!(PeriodType::recurring(3, 1, 32).is_err());
+    }
+
+    #[test]
+    fn test_end_of_month_periods() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        let months = PeriodType::recurring(1, 1, 31).unwrap();
+        assert_eq!(
+            Ok(vec![
+                (date(2024, 1, 31), date(2024, 2, 28)),
+                (date(2024, 2, 29), date(2024, 3, 30)),
+                (date(2024, 3, 31), date(2024, 4, 29)),
+            ]),
+            months.periods(&date(2024, 2, 1), &date(2024, 4, 29))
+        );
+    }
+
+    #[test]
+    fn test_unreported_shares() {
+        use crate::model::rsu::{
+            RestrictedStockUnitValue, RestrictedStockUnitVestingEvent,
+            RestrictedStockUnitVestingSchedule,
+        };
+
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let rsu_grants = vec![RestrictedStockUnitGrant::new(
+            "RSUs".to_string(),
+            date(2024, 1, 1),
+            RestrictedStockUnitValue::new(100, 1000),
+            RestrictedStockUnitVestingSchedule::new(
+                date(2024, 1, 1),
+                vec![
+                    RestrictedStockUnitVestingEvent::new(date(2024, 2, 1), 10),
+                    RestrictedStockUnitVestingEvent::new(date(2024, 8, 1), 5),
+                ],
+            ),
+            None,
+        )];
+
+        let report = Report::new(
+            &PreferredStockPrice::new(vec![]),
+            &vec![],
+            &rsu_grants,
+            ReportOptions {
+                period_type: PeriodType::Custom(vec![date(2024, 1, 1), date(2024, 7, 1)]),
+                columns: ColumnGroups::default(),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(10, report.lines[0].shares_total);
+        assert_eq!(5, report.unreported_shares);
+    }
 }
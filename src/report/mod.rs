@@ -1,17 +1,146 @@
+use std::io::Write;
+
+use chrono::NaiveDate;
+
+/// Vesting events changed by leaves and part-time work
+pub mod adjustment;
+
+/// Upcoming vesting events
+pub mod calendar;
+
+/// Value vesting each year across portfolios
+pub mod compare;
+
+/// Ownership after planned funding rounds
+pub mod dilution;
+
+/// Grants valued at an exit
+pub mod exit;
+
+/// Vested options that must be exercised soon
+pub mod expiry;
+
+/// Grants changed by an import
+pub mod import;
+
+/// Value vesting in each period
 pub mod incr;
+
+/// Percentile bands of value over simulated prices
+pub mod simulate;
+
+/// Vested and unvested shares per grant
+pub mod summary;
+
+/// What is lost by leaving the company
+pub mod termination;
+
+/// Vested and unvested value each day
 pub mod total;
 
-fn format_currency(cents: i32) -> String {
-    format!("{0}.{1:02}", cents / 100, cents % 100)
+/// `cents` as dollars and cents, e.g. 1234 as "12.34"
+pub fn format_currency(cents: impl Into<i64>) -> String {
+    let cents: i64 = cents.into();
+    let sign = if cents < 0 { "-" } else { "" };
+    format!(
+        "{0}{1}.{2:02}",
+        sign,
+        cents.unsigned_abs() / 100,
+        cents.unsigned_abs() % 100
+    )
+}
+
+/// Lowercase `name`, replacing anything but ASCII letters and digits with `-`
+pub fn slugify(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Date and number of shares or units of a vesting event
+pub type Vest = (NaiveDate, i32);
+
+/// Each vesting event in `before` but not `after`, and in `after` but not
+/// `before`
+pub fn event_changes(before: &[Vest], after: &[Vest]) -> (Vec<Vest>, Vec<Vest>) {
+    let removed = before
+        .iter()
+        .filter(|event| !after.contains(event))
+        .cloned()
+        .collect();
+    let added = after
+        .iter()
+        .filter(|event| !before.contains(event))
+        .cloned()
+        .collect();
+    (removed, added)
+}
+
+/// Write removed and added vesting events in date order, one per line, with
+/// the removed event first on the same date
+pub fn print_event_changes(
+    out: &mut impl Write,
+    removed: &[Vest],
+    added: &[Vest],
+) -> std::io::Result<()> {
+    let mut lines: Vec<(NaiveDate, char, i32)> = removed
+        .iter()
+        .map(|(date, number)| (*date, '-', *number))
+        .chain(added.iter().map(|(date, number)| (*date, '+', *number)))
+        .collect();
+    lines.sort_by_key(|(date, sign, _)| (*date, *sign == '+'));
+
+    for (date, sign, number) in lines {
+        writeln!(out, "  {} {}  {:>9}", sign, date, number)?;
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod test {
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_event_changes() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let before = vec![(date(2024, 1, 1), 10), (date(2024, 4, 1), 10)];
+        let after = vec![
+            (date(2024, 1, 1), 10),
+            (date(2024, 4, 1), 5),
+            (date(2024, 2, 1), 5),
+        ];
+
+        let (removed, added) = super::event_changes(&before, &after);
+        assert_eq!(vec![(date(2024, 4, 1), 10)], removed);
+        assert_eq!(vec![(date(2024, 4, 1), 5), (date(2024, 2, 1), 5)], added);
+
+        let mut out = Vec::new();
+        super::print_event_changes(&mut out, &removed, &added).unwrap();
+        assert_eq!(
+            "  + 2024-02-01          5\n  - 2024-04-01         10\n  + 2024-04-01          5\n",
+            String::from_utf8(out).unwrap()
+        );
+
+        assert_eq!((vec![], vec![]), super::event_changes(&before, &before));
+    }
+
     #[test]
     fn test_format_currency() {
         assert_eq!("0.00", super::format_currency(0));
         assert_eq!("0.01", super::format_currency(1));
         assert_eq!("0.10", super::format_currency(10));
         assert_eq!("1.00", super::format_currency(100));
+        assert_eq!("-1.50", super::format_currency(-150));
+        assert_eq!("-0.05", super::format_currency(-5));
+        assert_eq!(
+            "30000000000.00",
+            super::format_currency(3_000_000_000_000i64)
+        );
     }
 }
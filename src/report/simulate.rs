@@ -0,0 +1,274 @@
+use std::io::Write;
+
+use chrono::{Months, NaiveDate};
+use rand::{rngs::StdRng, SeedableRng};
+use rand_distr::{Distribution, StandardNormal};
+
+use crate::{
+    model::{
+        option::OptionGrant,
+        psp::{PreferredStockPrice, PreferredStockPriceValuation},
+        rsu::RestrictedStockUnitGrant,
+    },
+    report::{
+        format_currency,
+        total::{Granularity, Valuation, ValuationOptions},
+    },
+};
+
+const PERCENTILES: [f64; 3] = [0.1, 0.5, 0.9];
+
+/// How to simulate prices
+pub struct SimulationOptions {
+    /// Number of price paths to generate
+    pub paths: u32,
+
+    /// Expected annual return of the price, e.g. 0.1 for 10%
+    pub drift: f64,
+
+    /// Annualized volatility of the price, e.g. 0.5 for 50%
+    pub volatility: f64,
+
+    /// Seed for the random number generator, so runs are reproducible
+    pub seed: u64,
+
+    /// Last day to simulate, defaulting to the last vesting event
+    pub until: Option<NaiveDate>,
+
+    /// How often the bands have a row
+    pub granularity: Granularity,
+}
+
+/// Percentiles of the simulated value on a date, in cents
+pub struct SimulationItem {
+    /// The day valued
+    pub date: NaiveDate,
+
+    /// Vested value at the 10th, 50th and 90th percentiles
+    pub vested: Vec<i64>,
+
+    /// Total value at the 10th, 50th and 90th percentiles
+    pub total: Vec<i64>,
+}
+
+/// Percentile bands of equity value over simulated price paths
+pub struct Simulation {
+    /// The percentiles on each day valued, in date order
+    pub items: Vec<SimulationItem>,
+}
+
+/// Monthly prices following geometric Brownian motion from `start`, up to and
+/// including `until`
+fn price_path(
+    start: &PreferredStockPriceValuation,
+    until: &NaiveDate,
+    drift: f64,
+    volatility: f64,
+    rng: &mut StdRng,
+) -> Vec<PreferredStockPriceValuation> {
+    let dt = 1.0 / 12.0;
+    let mut price = start.value_cents() as f64;
+    let mut path = Vec::new();
+    let mut step = 1;
+
+    while let Some(date) = start
+        .date()
+        .checked_add_months(Months::new(step))
+        .filter(|date| date <= until)
+    {
+        let z: f64 = StandardNormal.sample(rng);
+        price *= ((drift - volatility.powi(2) / 2.0) * dt + volatility * dt.sqrt() * z).exp();
+        path.push(PreferredStockPriceValuation::new(
+            date,
+            price.round() as i32,
+        ));
+        step += 1;
+    }
+
+    path
+}
+
+/// Value at each of `PERCENTILES` using the nearest rank method
+fn percentiles(mut values: Vec<i64>) -> Vec<i64> {
+    values.sort();
+    PERCENTILES
+        .iter()
+        .map(|p| {
+            let rank = (p * values.len() as f64).ceil() as usize;
+            values[rank.saturating_sub(1)]
+        })
+        .collect()
+}
+
+impl Simulation {
+    /// Value the grants along `options.paths` simulated price paths, starting
+    /// from the last valuation. Without a valuation there is nothing to simulate.
+    pub fn new(
+        psp: &PreferredStockPrice,
+        option_grants: &Vec<OptionGrant>,
+        rsu_grants: &Vec<RestrictedStockUnitGrant>,
+        options: SimulationOptions,
+    ) -> Simulation {
+        // Prices are simulated from the last known price
+        let Some(start) = psp.last_valuation() else {
+            return Simulation { items: vec![] };
+        };
+
+        let until = options.until.unwrap_or_else(|| {
+            rsu_grants
+                .iter()
+                .map(|grant| grant.vesting_ends_on())
+                .chain(option_grants.iter().map(|grant| grant.vesting_ends_on()))
+                .max()
+                .unwrap_or(start.date())
+        });
+
+        let mut rng = StdRng::seed_from_u64(options.seed);
+
+        // Values per path, for each reported date
+        let mut dates: Vec<NaiveDate> = Vec::new();
+        let mut vested: Vec<Vec<i64>> = Vec::new();
+        let mut total: Vec<Vec<i64>> = Vec::new();
+
+        for _ in 0..options.paths {
+            let path = price_path(start, &until, options.drift, options.volatility, &mut rng);
+            let valuation = Valuation::new(
+                &psp.with_valuations(path),
+                option_grants,
+                rsu_grants,
+                ValuationOptions {
+                    from: None,
+                    to: Some(until),
+                    granularity: options.granularity.clone(),
+                },
+            );
+
+            if dates.is_empty() {
+                dates = valuation.items.iter().map(|item| item.date).collect();
+                vested = vec![Vec::new(); dates.len()];
+                total = vec![Vec::new(); dates.len()];
+            }
+
+            for (i, item) in valuation.items.iter().enumerate() {
+                vested[i].push(item.vested_total);
+                total[i].push(item.grant_total);
+            }
+        }
+
+        let items = dates
+            .into_iter()
+            .zip(vested.into_iter().zip(total))
+            .map(|(date, (vested, total))| SimulationItem {
+                date,
+                vested: percentiles(vested),
+                total: percentiles(total),
+            })
+            .collect();
+
+        Simulation { items }
+    }
+
+    /// Write the percentile bands as CSV, one row per date
+    pub fn print(&self, out: &mut impl Write) -> std::io::Result<()> {
+        out.write_all(b"Date,Vested P10,Vested P50,Vested P90,Total P10,Total P50,Total P90\n")?;
+
+        for item in &self.items {
+            out.write_fmt(format_args!(
+                "{},{},{}\n",
+                item.date,
+                item.vested
+                    .iter()
+                    .map(|v| format_currency(*v))
+                    .collect::<Vec<String>>()
+                    .join(","),
+                item.total
+                    .iter()
+                    .map(|v| format_currency(*v))
+                    .collect::<Vec<String>>()
+                    .join(","),
+            ))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_percentiles() {
+        assert_eq!(vec![1, 5, 9], percentiles((1..=10).rev().collect()));
+        assert_eq!(vec![7, 7, 7], percentiles(vec![7]));
+    }
+
+    #[test]
+    fn test_price_path_is_reproducible() {
+        let start =
+            PreferredStockPriceValuation::new(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(), 100);
+        let until = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+
+        let path = |seed| {
+            price_path(&start, &until, 0.1, 0.5, &mut StdRng::seed_from_u64(seed))
+                .iter()
+                .map(|v| v.value_cents())
+                .collect::<Vec<i32>>()
+        };
+
+        assert_eq!(12, path(1).len());
+        assert_eq!(path(1), path(1));
+        assert_ne!(path(1), path(2));
+    }
+
+    #[test]
+    fn test_high_volatility_does_not_overflow() {
+        use crate::model::rsu::{
+            RestrictedStockUnitValue, RestrictedStockUnitVestingEvent,
+            RestrictedStockUnitVestingSchedule,
+        };
+
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let psp = PreferredStockPrice::new(vec![PreferredStockPriceValuation::new(
+            date(2024, 1, 1),
+            100_000,
+        )]);
+        // 100,000 units at $1,000 is already more cents than an i32 holds
+        let rsu_grants = vec![RestrictedStockUnitGrant::new(
+            "Grant".to_string(),
+            date(2024, 1, 1),
+            RestrictedStockUnitValue::new(10_000, 1_000_000_000),
+            RestrictedStockUnitVestingSchedule::new(
+                date(2024, 1, 1),
+                vec![RestrictedStockUnitVestingEvent::new(
+                    date(2025, 1, 1),
+                    100_000,
+                )],
+            ),
+            None,
+        )];
+
+        let simulation = Simulation::new(
+            &psp,
+            &vec![],
+            &rsu_grants,
+            SimulationOptions {
+                paths: 100,
+                drift: 0.1,
+                volatility: 3.0,
+                seed: 0,
+                until: Some(date(2035, 1, 1)),
+                granularity: Granularity::Year,
+            },
+        );
+
+        assert!(simulation
+            .items
+            .iter()
+            .any(|item| item.total[2] > i32::MAX as i64));
+        assert!(simulation
+            .items
+            .iter()
+            .all(|item| item.total[0] <= item.total[2]));
+    }
+}
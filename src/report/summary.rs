@@ -0,0 +1,319 @@
+use std::io::Write;
+
+use chrono::NaiveDate;
+
+use crate::{
+    model::{option::OptionGrant, psp::PreferredStockPrice, rsu::RestrictedStockUnitGrant},
+    report::{format_currency, total::VestingState},
+};
+
+/// Vested and unvested shares or units of a grant, and their value in cents
+pub struct GrantSummary {
+    /// Name of the grant
+    pub name: String,
+
+    /// "Option" or "RSU"
+    pub kind: &'static str,
+
+    /// Shares or units granted
+    pub granted: i32,
+
+    /// Shares or units vested by the summary date
+    pub vested: i32,
+
+    /// Shares or units not yet vested
+    pub unvested: i32,
+
+    /// Value of the vested shares or units
+    pub vested_value: i64,
+
+    /// Value of the unvested shares or units
+    pub unvested_value: i64,
+
+    /// Date and number of the next vesting event, if any
+    pub next_vest: Option<(NaiveDate, i32)>,
+}
+
+/// Every grant as of a date
+pub struct Summary {
+    /// The day summarized
+    pub as_of: NaiveDate,
+
+    /// Preferred stock price on `as_of`, in cents, or `None` before the first
+    /// valuation, when nothing has a value
+    pub psp: Option<i32>,
+
+    /// Each grant, options first
+    pub grants: Vec<GrantSummary>,
+}
+
+fn next_vest<'a>(
+    as_of: &NaiveDate,
+    events: impl Iterator<Item = (&'a NaiveDate, i32)>,
+) -> Option<(NaiveDate, i32)> {
+    events
+        .filter(|(date, _)| *date > as_of)
+        .map(|(date, number)| (*date, number))
+        .next()
+}
+
+impl Summary {
+    /// Every grant as of `as_of`, valued at the price on that day
+    pub fn new(
+        psp: &PreferredStockPrice,
+        option_grants: &Vec<OptionGrant>,
+        rsu_grants: &Vec<RestrictedStockUnitGrant>,
+        as_of: NaiveDate,
+    ) -> Summary {
+        let mut state = VestingState::new(option_grants, rsu_grants);
+        state.advance_to(as_of);
+
+        let psp_on = psp.value_on(&as_of);
+
+        let mut grants: Vec<GrantSummary> = Vec::new();
+        for (i, grant) in option_grants.iter().enumerate() {
+            let vested = state.options_vested_units[i];
+            let unvested = state.options_unvested_units[i];
+            grants.push(GrantSummary {
+                name: grant.name.clone(),
+                kind: "Option",
+                granted: grant.value.number,
+                vested,
+                unvested,
+                vested_value: psp_on.map_or(0, |psp_on| state.option_value(i, vested, psp_on)),
+                unvested_value: psp_on.map_or(0, |psp_on| state.option_value(i, unvested, psp_on)),
+                next_vest: next_vest(
+                    &as_of,
+                    grant
+                        .vesting_schedule
+                        .events
+                        .iter()
+                        .map(|event| (&event.date, event.number)),
+                ),
+            });
+        }
+
+        for (i, grant) in rsu_grants.iter().enumerate() {
+            let vested = state.rsu_vested_units[i];
+            let unvested = state.rsu_unvested_units[i];
+            grants.push(GrantSummary {
+                name: grant.name.clone(),
+                kind: "RSU",
+                granted: grant.actual_total_units(),
+                vested,
+                unvested,
+                vested_value: vested as i64 * psp_on.unwrap_or(0) as i64,
+                unvested_value: unvested as i64 * psp_on.unwrap_or(0) as i64,
+                next_vest: next_vest(
+                    &as_of,
+                    grant
+                        .vesting_schedule
+                        .events
+                        .iter()
+                        .map(|event| (&event.date, event.number)),
+                ),
+            });
+        }
+
+        Summary {
+            as_of,
+            psp: psp_on,
+            grants,
+        }
+    }
+
+    /// Write the summary as a table, one row per grant
+    pub fn print(&self, out: &mut impl Write) -> std::io::Result<()> {
+        match self.psp {
+            Some(psp) => writeln!(
+                out,
+                "Summary as of {} (preferred stock price {})\n",
+                self.as_of,
+                format_currency(psp)
+            )?,
+            None => writeln!(
+                out,
+                "Summary as of {} (no preferred stock price yet, so nothing is valued)\n",
+                self.as_of
+            )?,
+        }
+
+        let name_width = self
+            .grants
+            .iter()
+            .map(|grant| grant.name.len())
+            .chain(std::iter::once("Total".len()))
+            .max()
+            .unwrap_or_default();
+
+        writeln!(
+            out,
+            "{:<name_width$}  {:<6}  {:>9}  {:>9}  {:>9}  {:>14}  {:>14}  Next Vest",
+            "Grant", "Type", "Granted", "Vested", "Unvested", "Vested Value", "Unvested Value",
+        )?;
+
+        for grant in &self.grants {
+            let next_vest = match grant.next_vest {
+                Some((date, number)) => format!("{} ({})", date, number),
+                None => "-".to_string(),
+            };
+            writeln!(
+                out,
+                "{:<name_width$}  {:<6}  {:>9}  {:>9}  {:>9}  {:>14}  {:>14}  {}",
+                grant.name,
+                grant.kind,
+                grant.granted,
+                grant.vested,
+                grant.unvested,
+                format_currency(grant.vested_value),
+                format_currency(grant.unvested_value),
+                next_vest,
+            )?;
+        }
+
+        let next_vest = self
+            .grants
+            .iter()
+            .filter_map(|grant| grant.next_vest)
+            .map(|(date, _)| date)
+            .min()
+            .map(|date| {
+                let number: i32 = self
+                    .grants
+                    .iter()
+                    .filter_map(|grant| grant.next_vest)
+                    .filter(|(d, _)| *d == date)
+                    .map(|(_, number)| number)
+                    .sum();
+                format!("{} ({})", date, number)
+            })
+            .unwrap_or_else(|| "-".to_string());
+
+        writeln!(
+            out,
+            "{:<name_width$}  {:<6}  {:>9}  {:>9}  {:>9}  {:>14}  {:>14}  {}",
+            "Total",
+            "",
+            self.grants.iter().map(|grant| grant.granted).sum::<i32>(),
+            self.grants.iter().map(|grant| grant.vested).sum::<i32>(),
+            self.grants.iter().map(|grant| grant.unvested).sum::<i32>(),
+            format_currency(
+                self.grants
+                    .iter()
+                    .map(|grant| grant.vested_value)
+                    .sum::<i64>()
+            ),
+            format_currency(
+                self.grants
+                    .iter()
+                    .map(|grant| grant.unvested_value)
+                    .sum::<i64>()
+            ),
+            next_vest,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::{
+        option::{
+            ExercisePeriod, OptionGrantValue, OptionGrantVestingEvent, OptionGrantVestingSchedule,
+        },
+        psp::PreferredStockPriceValuation,
+        rsu::{
+            RestrictedStockUnitValue, RestrictedStockUnitVestingEvent,
+            RestrictedStockUnitVestingSchedule,
+        },
+    };
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_summary() {
+        let psp = PreferredStockPrice::new(vec![PreferredStockPriceValuation::new(
+            date(2024, 1, 1),
+            300,
+        )]);
+        let option_grants = vec![OptionGrant::new(
+            "Options".to_string(),
+            date(2024, 1, 1),
+            OptionGrantValue::new(100, 400),
+            OptionGrantVestingSchedule::new(
+                date(2024, 1, 1),
+                (1..=4)
+                    .map(|month| OptionGrantVestingEvent::new(date(2024, month * 3, 1), 100))
+                    .collect(),
+            ),
+            ExercisePeriod::default(),
+            date(2034, 1, 1),
+            None,
+        )];
+
+        // The grant value implies 300 units, but only 200 are scheduled to vest
+        let rsu_grants = vec![RestrictedStockUnitGrant::new(
+            "RSUs".to_string(),
+            date(2024, 1, 1),
+            RestrictedStockUnitValue::new(100, 30000),
+            RestrictedStockUnitVestingSchedule::new(
+                date(2024, 1, 1),
+                vec![
+                    RestrictedStockUnitVestingEvent::new(date(2024, 6, 1), 100),
+                    RestrictedStockUnitVestingEvent::new(date(2024, 12, 1), 100),
+                ],
+            ),
+            None,
+        )];
+
+        let summary = Summary::new(&psp, &option_grants, &rsu_grants, date(2024, 7, 1));
+        assert_eq!(Some(300), summary.psp);
+
+        let options = &summary.grants[0];
+        assert_eq!(
+            (400, 200, 200),
+            (options.granted, options.vested, options.unvested)
+        );
+        assert_eq!(
+            (40000, 40000),
+            (options.vested_value, options.unvested_value)
+        );
+        assert_eq!(Some((date(2024, 9, 1), 100)), options.next_vest);
+
+        let rsus = &summary.grants[1];
+        assert_eq!((200, 100, 100), (rsus.granted, rsus.vested, rsus.unvested));
+        assert_eq!((30000, 30000), (rsus.vested_value, rsus.unvested_value));
+        assert_eq!(Some((date(2024, 12, 1), 100)), rsus.next_vest);
+    }
+
+    #[test]
+    fn test_summary_before_first_valuation() {
+        let psp = PreferredStockPrice::new(vec![PreferredStockPriceValuation::new(
+            date(2024, 1, 1),
+            300,
+        )]);
+        let rsu_grants = vec![RestrictedStockUnitGrant::new(
+            "RSUs".to_string(),
+            date(2023, 1, 1),
+            RestrictedStockUnitValue::new(100, 10000),
+            RestrictedStockUnitVestingSchedule::new(
+                date(2023, 1, 1),
+                vec![RestrictedStockUnitVestingEvent::new(date(2023, 6, 1), 100)],
+            ),
+            None,
+        )];
+
+        let summary = Summary::new(&psp, &vec![], &rsu_grants, date(2023, 7, 1));
+        assert_eq!(None, summary.psp);
+        assert_eq!(100, summary.grants[0].vested);
+        assert_eq!(0, summary.grants[0].vested_value);
+
+        let mut out = Vec::new();
+        summary.print(&mut out).unwrap();
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .starts_with("Summary as of 2023-07-01 (no preferred stock price yet"));
+    }
+}
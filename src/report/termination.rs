@@ -0,0 +1,234 @@
+use std::io::Write;
+
+use chrono::NaiveDate;
+
+use crate::{
+    model::{option::OptionGrant, psp::PreferredStockPrice, rsu::RestrictedStockUnitGrant},
+    report::format_currency,
+};
+
+/// Unvested shares or units of a grant forfeited on leaving
+pub struct Forfeiture {
+    /// Name of the grant
+    pub name: String,
+
+    /// "Option" or "RSU"
+    pub kind: &'static str,
+
+    /// Shares or units forfeited
+    pub shares: i32,
+
+    /// Value of the forfeited shares or units, in cents
+    pub value: i64,
+}
+
+/// Vested options of a grant that expire after leaving
+pub struct Expiry {
+    /// Name of the grant
+    pub name: String,
+
+    /// Vested options that expire
+    pub shares: i32,
+
+    /// Last day the vested options can be exercised
+    pub expires_on: NaiveDate,
+
+    /// Value of the options if they are not exercised, in cents
+    pub lost_value: i64,
+}
+
+/// What is lost by leaving the company: unvested shares, and vested options
+/// that expire if they are not exercised in time
+pub struct TerminationReport {
+    /// The last day at the company
+    pub terminated_on: NaiveDate,
+
+    /// Each grant with unvested shares or units, options first
+    pub forfeitures: Vec<Forfeiture>,
+
+    /// Each option grant with vested options, in the order given
+    pub expiries: Vec<Expiry>,
+}
+
+impl TerminationReport {
+    /// What leaving on `terminated_on` forfeits, valued at the price that day,
+    /// and the vested options that then expire, valued at the price on expiry
+    pub fn new(
+        psp: &PreferredStockPrice,
+        option_grants: &[OptionGrant],
+        rsu_grants: &[RestrictedStockUnitGrant],
+        terminated_on: NaiveDate,
+    ) -> TerminationReport {
+        let mut forfeitures = Vec::new();
+        let mut expiries = Vec::new();
+
+        for grant in option_grants {
+            let vested: i32 = grant
+                .vesting_schedule
+                .events
+                .iter()
+                .filter(|event| event.date <= terminated_on)
+                .map(|event| event.number)
+                .sum();
+            let unvested = grant.value.number - vested;
+            let spread = psp
+                .vest_price(&terminated_on, grant.vest_price)
+                .map_or(0, |price| (price - grant.value.exercise_price).max(0));
+
+            if unvested > 0 {
+                forfeitures.push(Forfeiture {
+                    name: grant.name.clone(),
+                    kind: "Option",
+                    shares: unvested,
+                    value: unvested as i64 * spread as i64,
+                });
+            }
+
+            if vested > 0 {
+                let expires_on = grant.terminate(terminated_on).exercisable_until();
+                let spread = psp
+                    .vest_price(&expires_on, grant.vest_price)
+                    .map_or(0, |price| (price - grant.value.exercise_price).max(0));
+                expiries.push(Expiry {
+                    name: grant.name.clone(),
+                    shares: vested,
+                    expires_on,
+                    lost_value: vested as i64 * spread as i64,
+                });
+            }
+        }
+
+        for grant in rsu_grants {
+            let unvested: i32 = grant
+                .vesting_schedule
+                .events
+                .iter()
+                .filter(|event| event.date > terminated_on)
+                .map(|event| event.number)
+                .sum();
+
+            if unvested > 0 {
+                forfeitures.push(Forfeiture {
+                    name: grant.name.clone(),
+                    kind: "RSU",
+                    shares: unvested,
+                    value: unvested as i64
+                        * psp
+                            .vest_price(&terminated_on, grant.vest_price)
+                            .unwrap_or(0) as i64,
+                });
+            }
+        }
+
+        TerminationReport {
+            terminated_on,
+            forfeitures,
+            expiries,
+        }
+    }
+
+    /// Write the forfeitures, then the expiries
+    pub fn print(&self, out: &mut impl Write) -> std::io::Result<()> {
+        writeln!(out, "Leaving on {}:", self.terminated_on)?;
+
+        for forfeiture in &self.forfeitures {
+            writeln!(
+                out,
+                "  {}: {} unvested {} shares are forfeited, worth {}",
+                forfeiture.name,
+                forfeiture.shares,
+                forfeiture.kind,
+                format_currency(forfeiture.value)
+            )?;
+        }
+
+        for expiry in &self.expiries {
+            writeln!(
+                out,
+                "  {}: {} vested options expire unexercised after {}, losing {}",
+                expiry.name,
+                expiry.shares,
+                expiry.expires_on,
+                format_currency(expiry.lost_value)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::{
+        option::{
+            ExercisePeriod, OptionGrantValue, OptionGrantVestingEvent, OptionGrantVestingSchedule,
+        },
+        psp::PreferredStockPriceValuation,
+        rsu::{
+            RestrictedStockUnitValue, RestrictedStockUnitVestingEvent,
+            RestrictedStockUnitVestingSchedule,
+        },
+    };
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_forfeitures_and_expiries() {
+        let psp = PreferredStockPrice::new(vec![
+            PreferredStockPriceValuation::new(date(2024, 1, 1), 300),
+            PreferredStockPriceValuation::new(date(2025, 1, 1), 500),
+        ]);
+        let option_grants = vec![OptionGrant::new(
+            "Options".to_string(),
+            date(2024, 1, 1),
+            OptionGrantValue::new(100, 400),
+            OptionGrantVestingSchedule::new(
+                date(2024, 1, 1),
+                (1..=4)
+                    .map(|month| OptionGrantVestingEvent::new(date(2024, month * 3, 1), 100))
+                    .collect(),
+            ),
+            ExercisePeriod::Months(6),
+            date(2034, 1, 1),
+            None,
+        )];
+        let rsu_grants = vec![RestrictedStockUnitGrant::new(
+            "RSUs".to_string(),
+            date(2024, 1, 1),
+            RestrictedStockUnitValue::new(100, 20000),
+            RestrictedStockUnitVestingSchedule::new(
+                date(2024, 1, 1),
+                vec![
+                    RestrictedStockUnitVestingEvent::new(date(2024, 6, 1), 100),
+                    RestrictedStockUnitVestingEvent::new(date(2024, 12, 1), 100),
+                ],
+            ),
+            None,
+        )];
+
+        let report = TerminationReport::new(&psp, &option_grants, &rsu_grants, date(2024, 7, 1));
+
+        let forfeitures: Vec<_> = report
+            .forfeitures
+            .iter()
+            .map(|f| (f.name.as_str(), f.kind, f.shares, f.value))
+            .collect();
+        assert_eq!(
+            vec![
+                ("Options", "Option", 200, 40000),
+                ("RSUs", "RSU", 100, 30000)
+            ],
+            forfeitures
+        );
+
+        // Vested options expire six months after leaving, valued at the price
+        // then
+        assert_eq!(1, report.expiries.len());
+        let expiry = &report.expiries[0];
+        assert_eq!((200, date(2025, 1, 1)), (expiry.shares, expiry.expires_on));
+        assert_eq!(80000, expiry.lost_value);
+    }
+}
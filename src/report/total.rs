@@ -1,41 +1,253 @@
-use std::fs::File;
 use std::io::Write;
-use std::path::PathBuf;
 
-use chrono::Days;
+use chrono::Datelike;
 use chrono::NaiveDate;
+use chrono::Weekday;
 
 use super::super::model::option::*;
 use super::super::model::psp::*;
 use super::super::model::rsu::*;
 use super::*;
 
+/// Value of the grants on a date, in cents
 pub struct ValuationItem {
-    date: NaiveDate,
-    psp: i32,
-    options_vested_total: i32,
-    options_unvested_total: i32,
-    rsu_vested_total: i32,
-    rsu_unvested_total: i32,
-    vested_total: i32,
-    unvested_total: i32,
-    grant_total: i32,
+    /// The day valued
+    pub date: NaiveDate,
+
+    /// Preferred stock price on the date
+    pub psp: i32,
+
+    /// Value of the vested options, net of exercise prices
+    pub options_vested_total: i64,
+
+    /// Value of the unvested options, net of exercise prices
+    pub options_unvested_total: i64,
+
+    /// Value of the vested RSUs
+    pub rsu_vested_total: i64,
+
+    /// Value of the unvested RSUs
+    pub rsu_unvested_total: i64,
+
+    /// Value of everything vested
+    pub vested_total: i64,
+
+    /// Value of everything not yet vested
+    pub unvested_total: i64,
+
+    /// Value of everything granted
+    pub grant_total: i64,
 }
 
+/// Value of the grants on each day, or at the end of each period
 pub struct Valuation {
-    items: Vec<ValuationItem>,
+    /// The days valued, in date order
+    pub items: Vec<ValuationItem>,
 }
 
-pub struct VestedOptionBundle {
-    number: i32,
-    exercise_price: i32,
+/// How often the valuation has a row
+#[derive(Debug, Default, Clone)]
+pub enum Granularity {
+    /// Every day
+    #[default]
+    Day,
+
+    /// The last day of each week
+    Week,
+
+    /// The last day of each month
+    Month,
+
+    /// The last day of each calendar quarter
+    Quarter,
+
+    /// The last day of each year
+    Year,
+}
+
+impl Granularity {
+    /// Whether `date` is the last day of its period. Weeks end on Sunday.
+    pub fn is_end_of_period(&self, date: &NaiveDate) -> bool {
+        let Some(next) = date.succ_opt() else {
+            return true;
+        };
+        match self {
+            Granularity::Day => true,
+            Granularity::Week => next.weekday() == Weekday::Mon,
+            Granularity::Month => next.month() != date.month(),
+            Granularity::Quarter => next.month0() / 3 != date.month0() / 3,
+            Granularity::Year => next.year() != date.year(),
+        }
+    }
+}
+
+/// Which days to value, and how often
+#[derive(Debug, Default)]
+pub struct ValuationOptions {
+    /// First day of the report, defaulting to when vesting commences
+    pub from: Option<NaiveDate>,
+
+    /// Last day of the report, defaulting to the last vesting event
+    pub to: Option<NaiveDate>,
+
+    /// How often the valuation has a row
+    pub granularity: Granularity,
+}
+
+/// Running count of vested and unvested units for each grant, in the order the
+/// grants were given.
+pub struct VestingState<'a> {
+    option_grants: &'a Vec<OptionGrant>,
+    rsu_grants: &'a Vec<RestrictedStockUnitGrant>,
+    applied_through: Option<NaiveDate>,
+
+    /// Vested options of each option grant
+    pub options_vested_units: Vec<i32>,
+
+    /// Granted options of each option grant that have not vested
+    pub options_unvested_units: Vec<i32>,
+
+    /// Vested units of each RSU grant
+    pub rsu_vested_units: Vec<i32>,
+
+    /// Granted units of each RSU grant that have not vested
+    pub rsu_unvested_units: Vec<i32>,
+}
+
+impl<'a> VestingState<'a> {
+    /// Nothing granted or vested yet; see `advance_to`
+    pub fn new(
+        option_grants: &'a Vec<OptionGrant>,
+        rsu_grants: &'a Vec<RestrictedStockUnitGrant>,
+    ) -> VestingState<'a> {
+        VestingState {
+            option_grants,
+            rsu_grants,
+            applied_through: None,
+            options_vested_units: vec![0; option_grants.len()],
+            options_unvested_units: vec![0; option_grants.len()],
+            rsu_vested_units: vec![0; rsu_grants.len()],
+            rsu_unvested_units: vec![0; rsu_grants.len()],
+        }
+    }
+
+    /// Apply every grant and vesting event dated on or before `date` that has
+    /// not already been applied.
+    pub fn advance_to(&mut self, date: NaiveDate) {
+        let after = self.applied_through;
+        let applies = |d: NaiveDate| d <= date && after.is_none_or(|after| d > after);
+
+        for (i, rsu_grant) in self.rsu_grants.iter().enumerate() {
+            if applies(rsu_grant.granted_on) {
+                self.rsu_unvested_units[i] += rsu_grant.actual_total_units();
+            }
+
+            for event in &rsu_grant.vesting_schedule.events {
+                if applies(event.date) {
+                    self.rsu_vested_units[i] += event.number;
+                    self.rsu_unvested_units[i] -= event.number;
+                }
+            }
+
+            // Unvested units are forfeited when leaving, including those of
+            // grants made after leaving
+            if rsu_grant
+                .terminated_on
+                .is_some_and(|left_on| left_on <= date)
+            {
+                self.rsu_unvested_units[i] = 0;
+            }
+        }
+
+        for (i, option_grant) in self.option_grants.iter().enumerate() {
+            if applies(option_grant.granted_on) {
+                self.options_unvested_units[i] += option_grant.value.number;
+            }
+
+            for event in &option_grant.vesting_schedule.events {
+                if applies(event.date) {
+                    self.options_vested_units[i] += event.number;
+                    self.options_unvested_units[i] -= event.number;
+                }
+            }
+
+            // Unvested options are forfeited when leaving, including those of
+            // grants made after leaving, and every option drops out the day
+            // after they can last be exercised, including any scheduled to
+            // vest later
+            if option_grant
+                .terminated_on
+                .is_some_and(|left_on| left_on <= date)
+            {
+                self.options_unvested_units[i] = 0;
+            }
+            if option_grant
+                .exercisable_until()
+                .succ_opt()
+                .is_some_and(|expired_on| expired_on <= date)
+            {
+                self.options_vested_units[i] = 0;
+                self.options_unvested_units[i] = 0;
+            }
+        }
+
+        if after.is_none_or(|after| date > after) {
+            self.applied_through = Some(date);
+        }
+    }
+
+    /// Value of `units` of the option grant at index `i` when the stock is
+    /// priced at `psp_on`.
+    pub fn option_value(&self, i: usize, units: i32, psp_on: i32) -> i64 {
+        units as i64 * (psp_on as i64 - self.option_grants[i].value.exercise_price as i64)
+    }
+
+    /// Value of the vested options when the stock is priced at `psp_on`
+    pub fn options_vested_total(&self, psp_on: i32) -> i64 {
+        self.options_vested_units
+            .iter()
+            .enumerate()
+            .map(|(i, units)| self.option_value(i, *units, psp_on))
+            .sum()
+    }
+
+    /// Value of the unvested options when the stock is priced at `psp_on`
+    pub fn options_unvested_total(&self, psp_on: i32) -> i64 {
+        self.options_unvested_units
+            .iter()
+            .enumerate()
+            .map(|(i, units)| self.option_value(i, *units, psp_on))
+            .sum()
+    }
+
+    /// Value of the vested RSUs when the stock is priced at `psp_on`
+    pub fn rsu_vested_total(&self, psp_on: i32) -> i64 {
+        self.rsu_vested_units
+            .iter()
+            .map(|units| *units as i64)
+            .sum::<i64>()
+            * psp_on as i64
+    }
+
+    /// Value of the unvested RSUs when the stock is priced at `psp_on`
+    pub fn rsu_unvested_total(&self, psp_on: i32) -> i64 {
+        self.rsu_unvested_units
+            .iter()
+            .map(|units| *units as i64)
+            .sum::<i64>()
+            * psp_on as i64
+    }
 }
 
 impl Valuation {
+    /// Value the grants on each day from when vesting commences through the
+    /// last vesting event, or the days in `options`. Days before the first
+    /// valuation are skipped.
     pub fn new(
         psp: &PreferredStockPrice,
         option_grants: &Vec<OptionGrant>,
         rsu_grants: &Vec<RestrictedStockUnitGrant>,
+        options: ValuationOptions,
     ) -> Valuation {
         // When vesting starts
         let start_date = rsu_grants
@@ -46,79 +258,45 @@ impl Valuation {
                     .iter()
                     .map(|option_grant| option_grant.vesting_schedule.commences_on),
             )
-            .min()
-            .unwrap();
+            .min();
 
         // When vested
         let end_date = rsu_grants
             .iter()
-            .map(|rsu_grant| rsu_grant.vesting_schedule.events.last().unwrap().date)
+            .map(|rsu_grant| rsu_grant.vesting_ends_on())
             .chain(
                 option_grants
                     .iter()
-                    .map(|option_grant| option_grant.vesting_schedule.events.last().unwrap().date),
+                    .map(|option_grant| option_grant.vesting_ends_on()),
             )
-            .max()
-            .unwrap();
+            .max();
 
-        let mut cursor = start_date;
-        let mut days: Vec<ValuationItem> = Vec::new();
-        let mut rsu_vested_units = 0;
-        let mut rsu_unvested_units = 0;
-        let mut options_vested_units: Vec<VestedOptionBundle> = vec![];
-        let mut options_unvested_units: Vec<VestedOptionBundle> = vec![];
-
-        while cursor <= end_date {
-            for rsu_grant in rsu_grants {
-                if rsu_grant.granted_on == cursor {
-                    rsu_unvested_units += rsu_grant.actual_total_units();
-                }
+        // Without grants there is nothing to value unless the dates are given
+        let (Some(start_date), Some(end_date)) =
+            (options.from.or(start_date), options.to.or(end_date))
+        else {
+            return Valuation { items: vec![] };
+        };
 
-                for event in &rsu_grant.vesting_schedule.events {
-                    if event.date == cursor {
-                        rsu_vested_units += event.number;
-                        rsu_unvested_units -= event.number;
-                    }
-                }
-            }
+        let mut days: Vec<ValuationItem> = Vec::new();
+        let mut state = VestingState::new(option_grants, rsu_grants);
 
-            for option_grant in option_grants {
-                if option_grant.granted_on == cursor {
-                    options_unvested_units.push(VestedOptionBundle {
-                        number: option_grant.value.number,
-                        exercise_price: option_grant.value.exercise_price,
-                    });
-                }
+        for cursor in start_date.iter_days().take_while(|date| date <= &end_date) {
+            state.advance_to(cursor);
 
-                for event in &option_grant.vesting_schedule.events {
-                    if event.date == cursor {
-                        options_vested_units.push(VestedOptionBundle {
-                            number: event.number,
-                            exercise_price: option_grant.value.exercise_price,
-                        });
-
-                        // Remove unvested options by adding a negative amount
-                        options_unvested_units.push(VestedOptionBundle {
-                            number: -event.number,
-                            exercise_price: option_grant.value.exercise_price,
-                        });
-                    }
-                }
+            // Non-daily reports only include the value at the end of each period
+            if cursor != end_date && !options.granularity.is_end_of_period(&cursor) {
+                continue;
             }
 
-            let psp_on = psp.value_on(&cursor);
-            let rsu_vested_total = rsu_vested_units * psp_on;
-            let rsu_unvested_total = rsu_unvested_units * psp_on;
-
-            let options_vested_total: i32 = options_vested_units
-                .iter()
-                .map(|bundle| bundle.number * (psp_on - bundle.exercise_price))
-                .sum();
-
-            let options_unvested_total: i32 = options_unvested_units
-                .iter()
-                .map(|bundle| bundle.number * (psp_on - bundle.exercise_price))
-                .sum();
+            // Days before the first valuation have no price to value at
+            let Some(psp_on) = psp.value_on(&cursor) else {
+                continue;
+            };
+            let rsu_vested_total = state.rsu_vested_total(psp_on);
+            let rsu_unvested_total = state.rsu_unvested_total(psp_on);
+            let options_vested_total = state.options_vested_total(psp_on);
+            let options_unvested_total = state.options_unvested_total(psp_on);
 
             let vested_total = rsu_vested_total + options_vested_total;
             let unvested_total = rsu_unvested_total + options_unvested_total;
@@ -136,20 +314,17 @@ impl Valuation {
                 vested_total,
                 grant_total,
             });
-
-            cursor = cursor.checked_add_days(Days::new(1)).unwrap();
         }
 
         Valuation { items: days }
     }
 
-    pub fn print_to_file(&self, output: &PathBuf) {
-        let mut file = File::create(output).unwrap();
-
-        file.write_all(b"Date,Preferred Stock Price,Options Vested Total,Options Unvested Total,RSUs Vested Total,RSUs Unvested Total,Vested Total,Unvested Total,Grand Total\n").unwrap();
+    /// Write the valuation as CSV, one row per day
+    pub fn print(&self, out: &mut impl Write) -> std::io::Result<()> {
+        out.write_all(b"Date,Preferred Stock Price,Options Vested Total,Options Unvested Total,RSUs Vested Total,RSUs Unvested Total,Vested Total,Unvested Total,Grand Total\n")?;
 
         for item in &self.items {
-            file.write_fmt(format_args!(
+            out.write_fmt(format_args!(
                 "{},{},{},{},{},{},{},{},{}\n",
                 item.date,
                 format_currency(item.psp),
@@ -160,8 +335,212 @@ impl Valuation {
                 format_currency(item.vested_total),
                 format_currency(item.unvested_total),
                 format_currency(item.grant_total)
-            ))
-            .unwrap();
+            ))?;
         }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_is_end_of_period() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        assert!(Granularity::Day.is_end_of_period(&date(2024, 5, 15)));
+
+        assert!(Granularity::Week.is_end_of_period(&date(2024, 5, 19)));
+        assert!(!Granularity::Week.is_end_of_period(&date(2024, 5, 20)));
+
+        assert!(Granularity::Month.is_end_of_period(&date(2024, 2, 29)));
+        assert!(!Granularity::Month.is_end_of_period(&date(2024, 2, 28)));
+
+        assert!(Granularity::Quarter.is_end_of_period(&date(2024, 6, 30)));
+        assert!(!Granularity::Quarter.is_end_of_period(&date(2024, 5, 31)));
+
+        assert!(Granularity::Year.is_end_of_period(&date(2024, 12, 31)));
+        assert!(!Granularity::Year.is_end_of_period(&date(2024, 11, 30)));
+    }
+
+    #[test]
+    fn test_vesting_state_advances_in_steps() {
+        use crate::model::rsu::{
+            RestrictedStockUnitValue, RestrictedStockUnitVestingEvent,
+            RestrictedStockUnitVestingSchedule,
+        };
+
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let rsu_grants = vec![RestrictedStockUnitGrant::new(
+            "RSUs".to_string(),
+            date(2024, 1, 1),
+            RestrictedStockUnitValue::new(100, 20000),
+            RestrictedStockUnitVestingSchedule::new(
+                date(2024, 1, 1),
+                vec![
+                    RestrictedStockUnitVestingEvent::new(date(2024, 6, 1), 100),
+                    RestrictedStockUnitVestingEvent::new(date(2024, 12, 1), 100),
+                ],
+            ),
+            None,
+        )];
+        let option_grants = vec![];
+
+        let mut state = VestingState::new(&option_grants, &rsu_grants);
+        state.advance_to(date(2023, 12, 31));
+        assert_eq!(
+            (0, 0),
+            (state.rsu_vested_units[0], state.rsu_unvested_units[0])
+        );
+
+        state.advance_to(date(2024, 6, 1));
+        assert_eq!(
+            (100, 100),
+            (state.rsu_vested_units[0], state.rsu_unvested_units[0])
+        );
+
+        // Advancing to a date already applied changes nothing
+        state.advance_to(date(2024, 3, 1));
+        state.advance_to(date(2024, 12, 1));
+        assert_eq!(
+            (200, 0),
+            (state.rsu_vested_units[0], state.rsu_unvested_units[0])
+        );
+        assert_eq!(60000, state.rsu_vested_total(300));
+    }
+
+    #[test]
+    fn test_expired_options_drop_out() {
+        use crate::model::option::{
+            ExercisePeriod, OptionGrantValue, OptionGrantVestingEvent, OptionGrantVestingSchedule,
+        };
+
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let grant = OptionGrant::new(
+            "Options".to_string(),
+            date(2024, 1, 1),
+            OptionGrantValue::new(100, 300),
+            OptionGrantVestingSchedule::new(
+                date(2024, 1, 1),
+                vec![
+                    OptionGrantVestingEvent::new(date(2024, 6, 1), 100),
+                    OptionGrantVestingEvent::new(date(2025, 6, 1), 100),
+                    OptionGrantVestingEvent::new(date(2026, 6, 1), 100),
+                ],
+            ),
+            ExercisePeriod::default(),
+            date(2025, 1, 1),
+            None,
+        );
+        let option_grants = vec![grant];
+        let rsu_grants = vec![];
+
+        let mut state = VestingState::new(&option_grants, &rsu_grants);
+        state.advance_to(date(2025, 1, 1));
+        assert_eq!(
+            (100, 200),
+            (
+                state.options_vested_units[0],
+                state.options_unvested_units[0]
+            )
+        );
+
+        // Neither the unvested options nor later vesting events survive expiry
+        state.advance_to(date(2025, 1, 2));
+        assert_eq!(
+            (0, 0),
+            (
+                state.options_vested_units[0],
+                state.options_unvested_units[0]
+            )
+        );
+        state.advance_to(date(2026, 12, 31));
+        assert_eq!(
+            (0, 0),
+            (
+                state.options_vested_units[0],
+                state.options_unvested_units[0]
+            )
+        );
+
+        // Advancing past expiry in one step is the same
+        let mut state = VestingState::new(&option_grants, &rsu_grants);
+        state.advance_to(date(2025, 7, 1));
+        assert_eq!(
+            (0, 0),
+            (
+                state.options_vested_units[0],
+                state.options_unvested_units[0]
+            )
+        );
+    }
+
+    #[test]
+    fn test_termination() {
+        use chrono::Months;
+
+        use crate::model::option::{
+            ExercisePeriod, OptionGrantValue, OptionGrantVestingEvent, OptionGrantVestingSchedule,
+        };
+
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let grant = |granted_on: NaiveDate| {
+            OptionGrant::new(
+                "Options".to_string(),
+                granted_on,
+                OptionGrantValue::new(100, 200),
+                OptionGrantVestingSchedule::new(
+                    granted_on,
+                    vec![
+                        OptionGrantVestingEvent::new(granted_on + Months::new(6), 100),
+                        OptionGrantVestingEvent::new(granted_on + Months::new(12), 100),
+                    ],
+                ),
+                ExercisePeriod::Days(90),
+                granted_on + Months::new(120),
+                None,
+            )
+        };
+        let units = |state: &VestingState| {
+            (
+                state.options_vested_units[0],
+                state.options_unvested_units[0],
+            )
+        };
+        let rsu_grants = vec![];
+
+        // Unvested options are forfeited on leaving, and vested options drop
+        // out once the post-termination exercise period ends
+        let option_grants = vec![grant(date(2024, 1, 1)).terminate(date(2024, 9, 1))];
+        let mut state = VestingState::new(&option_grants, &rsu_grants);
+        state.advance_to(date(2024, 9, 1));
+        assert_eq!((100, 0), units(&state));
+        state.advance_to(date(2024, 11, 30));
+        assert_eq!((100, 0), units(&state));
+        state.advance_to(date(2024, 12, 1));
+        assert_eq!((0, 0), units(&state));
+
+        // A grant made after leaving never counts
+        let option_grants = vec![grant(date(2024, 1, 1)).terminate(date(2023, 12, 1))];
+        let mut state = VestingState::new(&option_grants, &rsu_grants);
+        state.advance_to(date(2023, 12, 15));
+        state.advance_to(date(2024, 1, 1));
+        assert_eq!((0, 0), units(&state));
+    }
+
+    #[test]
+    fn test_valuation_without_grants() {
+        let psp = PreferredStockPrice::new(vec![]);
+        let options = ValuationOptions {
+            from: None,
+            to: None,
+            granularity: Granularity::Day,
+        };
+
+        let valuation = Valuation::new(&psp, &vec![], &vec![], options);
+
+        assert!(valuation.items.is_empty());
     }
 }